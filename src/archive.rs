@@ -0,0 +1,275 @@
+use std::{
+    fmt,
+    fs::File,
+    io::{self, BufRead, BufReader, Read},
+    path::Path,
+    sync::mpsc::{self, Receiver},
+};
+
+use sevenz_rust::{Password, SevenZReader};
+
+/// 展開したデータをスレッド間で受け渡す単位
+const CHUNK_SIZE: usize = 64 * 1024;
+
+/// 展開したデータを溜めておくチャンクの数。メモリ使用量は`CHUNK_SIZE * CHANNEL_CAPACITY`程度に抑えられる。
+const CHANNEL_CAPACITY: usize = 16;
+
+#[derive(Debug)]
+pub enum ArchiveError {
+    /// アーカイブファイルを開けなかった
+    Open(io::Error),
+    /// アーカイブに指定したファイルが含まれていない
+    MissingEntry(String),
+    /// アーカイブが壊れているか、対応していない形式である
+    Corrupt(sevenz_rust::Error),
+}
+
+impl fmt::Display for ArchiveError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ArchiveError::Open(_) => write!(f, "Failed to open archive"),
+            ArchiveError::MissingEntry(name) => write!(f, "Archive does not contain {}", name),
+            ArchiveError::Corrupt(_) => write!(f, "Archive is corrupt"),
+        }
+    }
+}
+
+impl std::error::Error for ArchiveError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            ArchiveError::Open(e) => Some(e),
+            ArchiveError::MissingEntry(_) => None,
+            ArchiveError::Corrupt(e) => Some(e),
+        }
+    }
+}
+
+/// ファイルの圧縮形式
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CompressionFormat {
+    SevenZip,
+    Gzip,
+    Bzip2,
+    Zstd,
+    /// 圧縮されていない
+    None,
+}
+
+impl CompressionFormat {
+    /// ファイル先頭のマジックナンバーから圧縮形式を判定する。
+    pub fn detect(header: &[u8]) -> CompressionFormat {
+        if header.starts_with(&[0x37, 0x7A, 0xBC, 0xAF, 0x27, 0x1C]) {
+            CompressionFormat::SevenZip
+        } else if header.starts_with(&[0x1F, 0x8B]) {
+            CompressionFormat::Gzip
+        } else if header.starts_with(b"BZh") {
+            CompressionFormat::Bzip2
+        } else if header.starts_with(&[0x28, 0xB5, 0x2F, 0xFD]) {
+            CompressionFormat::Zstd
+        } else {
+            CompressionFormat::None
+        }
+    }
+}
+
+/// データベースダンプのファイルを開く。圧縮されている場合は展開しながら読み出す。
+/// 7zアーカイブの場合は`entry`で指定したファイルを取り出す。
+pub fn open_database_dump(path: &Path, entry: &str) -> anyhow::Result<Box<dyn BufRead>> {
+    let mut file = BufReader::new(File::open(path)?);
+    let format = CompressionFormat::detect(file.fill_buf()?);
+
+    Ok(match format {
+        CompressionFormat::SevenZip => Box::new(BufReader::new(decompress_7z(path, entry)?)),
+        CompressionFormat::Gzip => {
+            Box::new(BufReader::new(flate2::bufread::MultiGzDecoder::new(file)))
+        }
+        CompressionFormat::Bzip2 => {
+            Box::new(BufReader::new(bzip2::bufread::MultiBzDecoder::new(file)))
+        }
+        CompressionFormat::Zstd => Box::new(BufReader::new(zstd::Decoder::with_buffer(file)?)),
+        CompressionFormat::None => Box::new(file),
+    })
+}
+
+/// 7zアーカイブから指定したファイルを取り出す。
+/// 展開は別スレッドで行われ、返り値から読み出した分だけ進む。
+pub fn decompress_7z(archive_file: &Path, filename: &str) -> Result<EntryReader, ArchiveError> {
+    let file = std::fs::File::open(archive_file).map_err(ArchiveError::Open)?;
+    let len = file.metadata().map_err(ArchiveError::Open)?.len();
+    let mut reader =
+        SevenZReader::new(file, len, Password::empty()).map_err(ArchiveError::Corrupt)?;
+
+    if !reader
+        .archive()
+        .files
+        .iter()
+        .any(|entry| entry.name() == filename)
+    {
+        return Err(ArchiveError::MissingEntry(filename.to_string()));
+    }
+
+    let filename = filename.to_string();
+    let (sender, receiver) = mpsc::sync_channel(CHANNEL_CAPACITY);
+
+    std::thread::spawn(move || {
+        let result = reader.for_each_entries(|entry, data| {
+            // ソリッドアーカイブでは後続のファイルを展開するために前のファイルを読み飛ばす必要がある
+            if entry.name() != filename {
+                io::copy(data, &mut io::sink())?;
+                return Ok(true);
+            }
+
+            let mut buf = vec![0; CHUNK_SIZE];
+            loop {
+                let len = data.read(&mut buf)?;
+                if len == 0 {
+                    return Ok(false);
+                }
+                if sender.send(Ok(Some(buf[..len].to_vec()))).is_err() {
+                    // 読み出し側が破棄された
+                    return Ok(false);
+                }
+            }
+        });
+
+        // 最後に結果を送り、途中でスレッドが終了した場合と区別できるようにする
+        let _ = sender.send(result.map(|_| None).map_err(ArchiveError::Corrupt));
+    });
+
+    Ok(EntryReader {
+        receiver,
+        chunk: Vec::new(),
+        position: 0,
+        finished: false,
+    })
+}
+
+/// アーカイブから取り出したファイルの内容を読み出す。
+/// 展開中のエラーは`ArchiveError`を内包した`io::Error`として返される。
+/// 展開するスレッドが最後まで送らずに終了した場合は`UnexpectedEof`のエラーになる。
+pub struct EntryReader {
+    /// 展開したデータを受け取る。`Ok(None)`は最後まで展開できたことを表す。
+    receiver: Receiver<Result<Option<Vec<u8>>, ArchiveError>>,
+    chunk: Vec<u8>,
+    position: usize,
+    finished: bool,
+}
+
+impl Read for EntryReader {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        while self.position == self.chunk.len() {
+            if self.finished {
+                return Ok(0);
+            }
+            match self.receiver.recv() {
+                Ok(Ok(Some(chunk))) => {
+                    self.chunk = chunk;
+                    self.position = 0;
+                }
+                Ok(Ok(None)) => self.finished = true,
+                Ok(Err(e)) => return Err(io::Error::other(e)),
+                Err(_) => {
+                    return Err(io::Error::new(
+                        io::ErrorKind::UnexpectedEof,
+                        "Decompression stopped before the end of the entry",
+                    ))
+                }
+            }
+        }
+
+        let len = buf.len().min(self.chunk.len() - self.position);
+        buf[..len].copy_from_slice(&self.chunk[self.position..self.position + len]);
+        self.position += len;
+        Ok(len)
+    }
+}
+
+#[test]
+fn test_decompress_7z() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    let content = "<mediawiki></mediawiki>".repeat(10000);
+    let source = temp_dir.path().join("pages.xml");
+    std::fs::write(&source, &content).unwrap();
+    let archive = temp_dir.path().join("pages.xml.7z");
+    sevenz_rust::compress_to_path(&source, &archive).unwrap();
+
+    let mut output = String::new();
+    decompress_7z(&archive, "pages.xml")
+        .unwrap()
+        .read_to_string(&mut output)
+        .unwrap();
+    assert_eq!(output, content);
+
+    assert!(matches!(
+        decompress_7z(&archive, "missing.xml"),
+        Err(ArchiveError::MissingEntry(_))
+    ));
+
+    std::fs::write(&archive, b"not a 7z archive").unwrap();
+    assert!(matches!(
+        decompress_7z(&archive, "pages.xml"),
+        Err(ArchiveError::Corrupt(_))
+    ));
+}
+
+#[test]
+fn test_entry_reader_hang_up() {
+    let (sender, receiver) = mpsc::sync_channel(CHANNEL_CAPACITY);
+    let mut reader = EntryReader {
+        receiver,
+        chunk: Vec::new(),
+        position: 0,
+        finished: false,
+    };
+
+    // 最後の結果を送らずにスレッドが終了した場合
+    sender.send(Ok(Some(b"<mediawiki>".to_vec()))).unwrap();
+    drop(sender);
+    let mut output = Vec::new();
+    let error = reader.read_to_end(&mut output).unwrap_err();
+    assert_eq!(error.kind(), io::ErrorKind::UnexpectedEof);
+    assert_eq!(output, b"<mediawiki>");
+}
+
+#[test]
+fn test_open_database_dump() {
+    use std::io::Write;
+
+    let temp_dir = tempfile::tempdir().unwrap();
+    let content = "<mediawiki></mediawiki>";
+
+    let read = |path: &Path| {
+        let mut output = String::new();
+        open_database_dump(path, "pages.xml")
+            .unwrap()
+            .read_to_string(&mut output)
+            .unwrap();
+        output
+    };
+
+    let plain = temp_dir.path().join("pages.xml");
+    std::fs::write(&plain, content).unwrap();
+    assert_eq!(read(&plain), content);
+
+    let gzip = temp_dir.path().join("pages.xml.gz");
+    let mut encoder =
+        flate2::write::GzEncoder::new(File::create(&gzip).unwrap(), Default::default());
+    encoder.write_all(content.as_bytes()).unwrap();
+    encoder.finish().unwrap();
+    assert_eq!(read(&gzip), content);
+
+    let bzip2 = temp_dir.path().join("pages.xml.bz2");
+    let mut encoder =
+        bzip2::write::BzEncoder::new(File::create(&bzip2).unwrap(), Default::default());
+    encoder.write_all(content.as_bytes()).unwrap();
+    encoder.finish().unwrap();
+    assert_eq!(read(&bzip2), content);
+
+    let zstd = temp_dir.path().join("pages.xml.zst");
+    std::fs::write(&zstd, zstd::encode_all(content.as_bytes(), 0).unwrap()).unwrap();
+    assert_eq!(read(&zstd), content);
+
+    let sevenz = temp_dir.path().join("pages.xml.7z");
+    sevenz_rust::compress_to_path(&plain, &sevenz).unwrap();
+    assert_eq!(read(&sevenz), content);
+}
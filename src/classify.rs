@@ -0,0 +1,105 @@
+use serde::Deserialize;
+
+use crate::Page;
+
+/// 単語の分類。出力形式ごとにIMEの品詞に対応付けられる。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum WordCategory {
+    /// 人物
+    Person,
+    /// 地域・場所
+    Place,
+    /// 組織・勢力
+    Organization,
+    /// アイテム・武器・素材など
+    Noun,
+    /// 分類できなかった固有名詞
+    ProperNoun,
+}
+
+const PERSON_INFOBOXES: &[&str] = &["Character Infobox", "NPC Infobox"];
+const PLACE_INFOBOXES: &[&str] = &["Location Infobox", "Region Infobox", "Domain Infobox"];
+const ORGANIZATION_INFOBOXES: &[&str] = &["Faction Infobox", "Organization Infobox"];
+const NOUN_INFOBOXES: &[&str] = &[
+    "Item Infobox",
+    "Weapon Infobox",
+    "Material Infobox",
+    "Artifact Infobox",
+    "Food Infobox",
+    "Furnishing Infobox",
+];
+
+const PERSON_CATEGORIES: &[&str] = &["Characters", "Playable Characters", "NPCs"];
+const PLACE_CATEGORIES: &[&str] = &["Regions", "Locations", "Areas", "Subareas"];
+const ORGANIZATION_CATEGORIES: &[&str] = &["Factions", "Organizations"];
+const NOUN_CATEGORIES: &[&str] = &[
+    "Items",
+    "Weapons",
+    "Materials",
+    "Artifacts",
+    "Food",
+    "Furnishings",
+];
+
+/// インフォボックスとカテゴリの分類。先にあるものを優先する。
+const RULES: &[(&[&str], &[&str], WordCategory)] = &[
+    (PERSON_INFOBOXES, PERSON_CATEGORIES, WordCategory::Person),
+    (PLACE_INFOBOXES, PLACE_CATEGORIES, WordCategory::Place),
+    (
+        ORGANIZATION_INFOBOXES,
+        ORGANIZATION_CATEGORIES,
+        WordCategory::Organization,
+    ),
+    (NOUN_INFOBOXES, NOUN_CATEGORIES, WordCategory::Noun),
+];
+
+/// インフォボックスの名前から単語を分類する。
+pub fn classify_infobox(infobox: &str) -> Option<WordCategory> {
+    RULES
+        .iter()
+        .find(|(infoboxes, _, _)| infoboxes.contains(&infobox))
+        .map(|&(_, _, category)| category)
+}
+
+/// ページのインフォボックスとカテゴリから単語を分類する。インフォボックスを優先する。
+pub fn classify(page: &Page) -> WordCategory {
+    let text = &page.revision.text;
+
+    for &(infoboxes, _, category) in RULES {
+        if text
+            .infoboxes
+            .iter()
+            .any(|infobox| infoboxes.contains(&infobox.as_str()))
+        {
+            return category;
+        }
+    }
+
+    for &(_, categories, category) in RULES {
+        if text
+            .categories
+            .iter()
+            .any(|c| categories.contains(&c.as_str()))
+        {
+            return category;
+        }
+    }
+
+    WordCategory::ProperNoun
+}
+
+#[test]
+fn test_classify() {
+    let mut page = Page::default();
+    assert_eq!(classify(&page), WordCategory::ProperNoun);
+
+    page.revision.text.categories = vec!["Liyue".to_string(), "Regions".to_string()];
+    assert_eq!(classify(&page), WordCategory::Place);
+
+    page.revision.text.infoboxes = vec!["Character Infobox".to_string()];
+    assert_eq!(classify(&page), WordCategory::Person);
+
+    assert_eq!(classify_infobox("Weapon Infobox"), Some(WordCategory::Noun));
+    assert_eq!(classify_infobox("Quest Infobox"), None);
+}
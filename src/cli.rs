@@ -0,0 +1,146 @@
+use std::path::PathBuf;
+
+use clap::{Args, Parser, Subcommand, ValueEnum};
+
+use crate::{profile::WikiProfile, romaji::RomajiMode, writer::OutputFormat, Language, PageFilter};
+
+/// WikiのデータベースダンプからIME辞書を作成する。
+#[derive(Debug, Parser)]
+#[command(version)]
+pub struct Cli {
+    /// 警告とエラー以外のメッセージを表示しない
+    #[arg(short, long, global = true, conflicts_with = "verbose")]
+    pub quiet: bool,
+    /// 詳細なメッセージを表示する
+    #[arg(short, long, global = true)]
+    pub verbose: bool,
+    /// 省略した場合は`generate`を既定の設定で実行する
+    #[command(subcommand)]
+    pub command: Option<Command>,
+}
+
+impl Cli {
+    pub fn log_level(&self) -> log::LevelFilter {
+        if self.quiet {
+            log::LevelFilter::Warn
+        } else if self.verbose {
+            log::LevelFilter::Debug
+        } else {
+            log::LevelFilter::Info
+        }
+    }
+}
+
+#[derive(Debug, Subcommand)]
+pub enum Command {
+    /// 辞書を生成する
+    Generate(GenerateArgs),
+    /// データベースダンプをダウンロードしてキャッシュするだけで、辞書は生成しない
+    Download(WikiArgs),
+    /// 生成される辞書の統計を表示する
+    Stats(StatsArgs),
+    /// 単語または読みで辞書を検索する
+    Lookup(LookupArgs),
+}
+
+#[derive(Debug, Default, Args)]
+pub struct GenerateArgs {
+    #[command(flatten)]
+    pub source: SourceArgs,
+    /// 出力形式 (google, msime, plist, skk, rime-ja, sogou, rime-zh-hans, rime-zh-hant, korean, fcitx5-hangul)
+    #[arg(short, long, default_value = "google")]
+    pub format: OutputFormat,
+    /// 出力先のファイル。省略した場合は標準出力に書き出す
+    #[arg(short, long)]
+    pub output: Option<PathBuf>,
+    /// 品詞の付け方
+    #[arg(long, value_enum, default_value_t)]
+    pub pos_mode: PosMode,
+}
+
+#[derive(Debug, Args)]
+pub struct StatsArgs {
+    #[command(flatten)]
+    pub source: SourceArgs,
+    /// 辞書の言語
+    #[arg(short, long, value_enum, default_value_t)]
+    pub language: Language,
+}
+
+#[derive(Debug, Args)]
+pub struct LookupArgs {
+    /// 検索する単語または読み
+    pub query: String,
+    #[command(flatten)]
+    pub source: SourceArgs,
+    /// 辞書の言語
+    #[arg(short, long, value_enum, default_value_t)]
+    pub language: Language,
+}
+
+/// 辞書の元になるデータベースダンプの指定
+#[derive(Debug, Default, Args)]
+pub struct SourceArgs {
+    /// ダウンロード済みのデータベースダンプ。省略した場合はWikiからダウンロードする
+    #[arg(short, long)]
+    pub input: Option<PathBuf>,
+    #[command(flatten)]
+    pub wiki: WikiArgs,
+    /// 読み込む名前空間の番号 (複数指定可)。既定は標準名前空間 (0) のみ
+    #[arg(long = "namespace")]
+    pub namespaces: Vec<i32>,
+    /// リダイレクトページも読み込む
+    #[arg(long)]
+    pub include_redirects: bool,
+    /// ローマ字に変換できない文字がある単語を辞書に含めない。既定では警告を表示して、その文字をそのまま読みに残す
+    #[arg(long)]
+    pub strict_romaji: bool,
+    /// `ti`、`tu`、`di`、`du`の読み方
+    #[arg(long, value_enum, default_value_t)]
+    pub romaji_mode: RomajiMode,
+    /// 生成した辞書に適用する修正の設定ファイル (TOML)
+    #[arg(long)]
+    pub overrides: Option<PathBuf>,
+}
+
+impl SourceArgs {
+    pub fn filter(&self) -> PageFilter {
+        let mut filter = PageFilter {
+            include_redirects: self.include_redirects,
+            ..Default::default()
+        };
+        if !self.namespaces.is_empty() {
+            filter.namespaces = self.namespaces.clone();
+        }
+        filter
+    }
+}
+
+#[derive(Debug, Default, Args)]
+pub struct WikiArgs {
+    /// 組み込みのWiki (genshin, hsr, zzz)。既定はgenshin
+    #[arg(long, conflicts_with = "wiki_config")]
+    pub wiki: Option<String>,
+    /// Wikiの設定ファイル (TOML)
+    #[arg(long)]
+    pub wiki_config: Option<PathBuf>,
+}
+
+impl WikiArgs {
+    pub fn profile(&self) -> anyhow::Result<WikiProfile> {
+        match (&self.wiki, &self.wiki_config) {
+            (_, Some(path)) => WikiProfile::load(path),
+            (Some(name), None) => WikiProfile::builtin(name),
+            (None, None) => WikiProfile::builtin("genshin"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+pub enum PosMode {
+    /// ページのインフォボックスとカテゴリから品詞を推定する
+    #[default]
+    Classified,
+    /// すべての単語を固有名詞にする
+    ProperNoun,
+}
@@ -0,0 +1,93 @@
+use std::collections::HashMap;
+
+use crate::{classify::WordCategory, IMEDictionaryEntry};
+
+/// 同じ単語に異なる読みが付けられていること
+#[derive(Debug, PartialEq)]
+pub struct ReadingCollision {
+    pub word: String,
+    pub yomis: Vec<String>,
+}
+
+/// 単語と読みが同じエントリを1つにまとめ、出典のページを統合する。
+/// エントリの順序は最初に現れた位置のまま保たれる。
+/// 返り値の2つ目は、同じ単語に複数の読みが付けられているものの一覧。
+pub fn deduplicate(
+    entries: Vec<IMEDictionaryEntry>,
+) -> (Vec<IMEDictionaryEntry>, Vec<ReadingCollision>) {
+    let mut merged = Vec::<IMEDictionaryEntry>::new();
+    let mut indices = HashMap::<(String, String), usize>::new();
+
+    for entry in entries {
+        let key = (entry.word.clone(), entry.yomi.clone());
+        if let Some(&i) = indices.get(&key) {
+            let existing = &mut merged[i];
+            for page_title in entry.page_titles {
+                if !existing.page_titles.contains(&page_title) {
+                    existing.page_titles.push(page_title);
+                }
+            }
+            // 分類できたページがあればその分類を使う
+            if existing.category == WordCategory::ProperNoun {
+                existing.category = entry.category;
+            }
+            if existing.romanization.is_none() {
+                existing.romanization = entry.romanization;
+            }
+        } else {
+            indices.insert(key, merged.len());
+            merged.push(entry);
+        }
+    }
+
+    let mut collisions = Vec::<ReadingCollision>::new();
+    let mut collision_indices = HashMap::<&str, usize>::new();
+    for entry in &merged {
+        if let Some(&i) = collision_indices.get(entry.word.as_str()) {
+            collisions[i].yomis.push(entry.yomi.clone());
+        } else {
+            collision_indices.insert(&entry.word, collisions.len());
+            collisions.push(ReadingCollision {
+                word: entry.word.clone(),
+                yomis: vec![entry.yomi.clone()],
+            });
+        }
+    }
+    collisions.retain(|collision| collision.yomis.len() > 1);
+
+    (merged, collisions)
+}
+
+#[test]
+fn test_deduplicate() {
+    use crate::test_entry;
+
+    let entry = |word, yomi, page_title, category| IMEDictionaryEntry {
+        category,
+        ..test_entry(word, yomi, page_title)
+    };
+
+    let (entries, collisions) = deduplicate(vec![
+        entry("璃月", "りーゆえ", "Story Quest", WordCategory::ProperNoun),
+        entry("モンド", "もんど", "Mondstadt", WordCategory::Place),
+        entry("璃月", "りーゆえ", "Liyue", WordCategory::Place),
+        entry("璃月", "りいゆえ", "Liyue Harbor", WordCategory::Place),
+        entry("璃月", "りーゆえ", "Liyue", WordCategory::Place),
+    ]);
+
+    assert_eq!(entries.len(), 3);
+    assert_eq!(entries[0].word, "璃月");
+    assert_eq!(entries[0].yomi, "りーゆえ");
+    assert_eq!(entries[0].page_titles, vec!["Story Quest", "Liyue"]);
+    assert_eq!(entries[0].category, WordCategory::Place);
+    assert_eq!(entries[1].word, "モンド");
+    assert_eq!(entries[2].yomi, "りいゆえ");
+
+    assert_eq!(
+        collisions,
+        vec![ReadingCollision {
+            word: "璃月".to_string(),
+            yomis: vec!["りーゆえ".to_string(), "りいゆえ".to_string()],
+        }]
+    );
+}
@@ -0,0 +1,322 @@
+use std::path::{Path, PathBuf};
+
+use anyhow::Context;
+use reqwest::{header, StatusCode};
+use tokio::{
+    fs::{self, File, OpenOptions},
+    io::AsyncWriteExt,
+};
+
+/// キャッシュしたファイルを検証するためのHTTPヘッダーの値
+#[derive(Debug, Default, Clone, PartialEq)]
+struct Validators {
+    etag: Option<String>,
+    last_modified: Option<String>,
+}
+
+impl Validators {
+    fn from_response(response: &reqwest::Response) -> Validators {
+        let header = |name| {
+            response
+                .headers()
+                .get(name)
+                .and_then(|value| value.to_str().ok())
+                .map(|value| value.to_string())
+        };
+        Validators {
+            etag: header(header::ETAG),
+            last_modified: header(header::LAST_MODIFIED),
+        }
+    }
+
+    /// `If-Range`に使う値。ETagを優先する。
+    fn if_range(&self) -> Option<&str> {
+        self.etag.as_deref().or(self.last_modified.as_deref())
+    }
+
+    async fn load(path: &Path) -> Option<Validators> {
+        let text = fs::read_to_string(path).await.ok()?;
+        let mut validators = Validators::default();
+        for line in text.lines() {
+            match line.split_once(": ") {
+                Some(("ETag", value)) => validators.etag = Some(value.to_string()),
+                Some(("Last-Modified", value)) => {
+                    validators.last_modified = Some(value.to_string())
+                }
+                _ => {}
+            }
+        }
+        Some(validators)
+    }
+
+    async fn save(&self, path: &Path) -> anyhow::Result<()> {
+        let mut text = String::new();
+        if let Some(etag) = &self.etag {
+            text.push_str(&format!("ETag: {}\n", etag));
+        }
+        if let Some(last_modified) = &self.last_modified {
+            text.push_str(&format!("Last-Modified: {}\n", last_modified));
+        }
+        fs::write(path, text)
+            .await
+            .with_context(|| format!("Failed to write {}", path.display()))
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DownloadStatus {
+    /// キャッシュしたファイルが最新だった
+    NotModified,
+    /// ファイル全体をダウンロードした
+    Downloaded,
+    /// 途中までダウンロードしたファイルの続きをダウンロードした
+    Resumed,
+}
+
+/// ダウンロードしたファイルを保存するディレクトリ (`$XDG_CACHE_HOME/genshin-ime-dictionary`など)
+pub fn cache_dir() -> anyhow::Result<PathBuf> {
+    Ok(dirs::cache_dir()
+        .context("Failed to determine cache directory")?
+        .join(env!("CARGO_PKG_NAME")))
+}
+
+/// `url`のファイルを`path`にダウンロードする。
+///
+/// `path`にダウンロード済みのファイルがある場合は、サーバー上のファイルが更新されているときだけダウンロードし直す。
+/// 途中で中断したダウンロードは`<path>.part`に保存され、次回はその続きからダウンロードする。
+/// ただし、途中までのファイルを検証できない (`ETag`も`Last-Modified`もない) 場合や、
+/// サーバーが返した範囲が続きと一致しない場合は最初からダウンロードし直す。
+pub async fn download_cached(
+    client: &reqwest::Client,
+    url: &str,
+    path: &Path,
+) -> anyhow::Result<DownloadStatus> {
+    let meta_path = with_suffix(path, ".meta");
+    let part_path = with_suffix(path, ".part");
+    let part_meta_path = with_suffix(path, ".part.meta");
+
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)
+            .await
+            .with_context(|| format!("Failed to create {}", parent.display()))?;
+    }
+
+    let mut part_len = match fs::metadata(&part_path).await {
+        Ok(metadata) => metadata.len(),
+        Err(_) => 0,
+    };
+    let part_validators = Validators::load(&part_meta_path).await.unwrap_or_default();
+    if part_len > 0 && part_validators.if_range().is_none() {
+        // サーバー上のファイルが変わっていないか確かめられないので続きからはダウンロードしない
+        fs::remove_file(&part_path)
+            .await
+            .with_context(|| format!("Failed to remove {}", part_path.display()))?;
+        part_len = 0;
+    }
+
+    let mut request = client.get(url);
+    if let (true, Some(if_range)) = (part_len > 0, part_validators.if_range()) {
+        request = request
+            .header(header::RANGE, format!("bytes={}-", part_len))
+            .header(header::IF_RANGE, if_range);
+    } else if path.exists() {
+        if let Some(validators) = Validators::load(&meta_path).await {
+            if let Some(etag) = &validators.etag {
+                request = request.header(header::IF_NONE_MATCH, etag);
+            }
+            if let Some(last_modified) = &validators.last_modified {
+                request = request.header(header::IF_MODIFIED_SINCE, last_modified);
+            }
+        }
+    }
+
+    let mut response = request
+        .send()
+        .await
+        .with_context(|| format!("Failed to request {}", url))?;
+
+    let (status, mut file) = match response.status() {
+        StatusCode::NOT_MODIFIED => return Ok(DownloadStatus::NotModified),
+        StatusCode::PARTIAL_CONTENT if part_len > 0 => {
+            if content_range_start(&response) != Some(part_len) {
+                // 要求した続きと異なる範囲が返ってきたので最初からやり直す
+                fs::remove_file(&part_path)
+                    .await
+                    .with_context(|| format!("Failed to remove {}", part_path.display()))?;
+                return Box::pin(download_cached(client, url, path)).await;
+            }
+            let file = OpenOptions::new()
+                .append(true)
+                .open(&part_path)
+                .await
+                .with_context(|| format!("Failed to open {}", part_path.display()))?;
+            (DownloadStatus::Resumed, file)
+        }
+        StatusCode::RANGE_NOT_SATISFIABLE if part_len > 0 => {
+            // 途中までのファイルが壊れているので最初からやり直す
+            fs::remove_file(&part_path)
+                .await
+                .with_context(|| format!("Failed to remove {}", part_path.display()))?;
+            return Box::pin(download_cached(client, url, path)).await;
+        }
+        _ => {
+            response = response
+                .error_for_status()
+                .with_context(|| format!("Failed to download {}", url))?;
+            Validators::from_response(&response)
+                .save(&part_meta_path)
+                .await?;
+            let file = File::create(&part_path)
+                .await
+                .with_context(|| format!("Failed to create {}", part_path.display()))?;
+            (DownloadStatus::Downloaded, file)
+        }
+    };
+
+    while let Some(chunk) = response
+        .chunk()
+        .await
+        .with_context(|| format!("Failed to download {}", url))?
+    {
+        file.write_all(&chunk)
+            .await
+            .with_context(|| format!("Failed to write {}", part_path.display()))?;
+    }
+    file.flush()
+        .await
+        .with_context(|| format!("Failed to write {}", part_path.display()))?;
+
+    fs::rename(&part_path, path)
+        .await
+        .with_context(|| format!("Failed to rename {}", part_path.display()))?;
+    fs::rename(&part_meta_path, &meta_path)
+        .await
+        .with_context(|| format!("Failed to rename {}", part_meta_path.display()))?;
+
+    Ok(status)
+}
+
+/// `Content-Range: bytes <start>-<end>/<size>`の`<start>`
+fn content_range_start(response: &reqwest::Response) -> Option<u64> {
+    let value = response
+        .headers()
+        .get(header::CONTENT_RANGE)?
+        .to_str()
+        .ok()?;
+    let (start, _) = value.strip_prefix("bytes ")?.split_once('-')?;
+    start.parse().ok()
+}
+
+fn with_suffix(path: &Path, suffix: &str) -> PathBuf {
+    let mut path = path.as_os_str().to_owned();
+    path.push(suffix);
+    path.into()
+}
+
+/// テスト用のHTTPサーバーを起動する。`ETag`、`If-None-Match`、`Range`、`If-Range`だけを解釈する。
+/// `If-Range`のない`Range`も受け付けるので、クライアントが検証せずに続きを要求すると壊れたファイルになる。
+#[cfg(test)]
+async fn spawn_test_server(body: &'static [u8], etag: &'static str) -> String {
+    use tokio::io::AsyncReadExt;
+
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let address = listener.local_addr().unwrap();
+
+    tokio::spawn(async move {
+        loop {
+            let (mut stream, _) = listener.accept().await.unwrap();
+
+            let mut request = Vec::new();
+            let mut buf = [0; 1024];
+            while !request.ends_with(b"\r\n\r\n") {
+                let len = stream.read(&mut buf).await.unwrap();
+                if len == 0 {
+                    break;
+                }
+                request.extend_from_slice(&buf[..len]);
+            }
+            let request = String::from_utf8(request).unwrap();
+            let header = |name: &str| {
+                request.lines().find_map(|line| {
+                    let (key, value) = line.split_once(": ")?;
+                    key.eq_ignore_ascii_case(name).then(|| value.to_string())
+                })
+            };
+
+            let range_start = header("Range")
+                .and_then(|range| {
+                    range
+                        .strip_prefix("bytes=")?
+                        .strip_suffix('-')?
+                        .parse()
+                        .ok()
+                })
+                .filter(|_| header("If-Range").is_none_or(|value| value == etag));
+
+            let (status, content, content_range) =
+                if header("If-None-Match").as_deref() == Some(etag) {
+                    ("304 Not Modified", &body[..0], String::new())
+                } else if let Some(start) = range_start {
+                    let content_range = format!(
+                        "Content-Range: bytes {}-{}/{}\r\n",
+                        start,
+                        body.len() - 1,
+                        body.len()
+                    );
+                    ("206 Partial Content", &body[start..], content_range)
+                } else {
+                    ("200 OK", body, String::new())
+                };
+
+            let response = format!(
+                "HTTP/1.1 {}\r\nETag: {}\r\n{}Content-Length: {}\r\nConnection: close\r\n\r\n",
+                status,
+                etag,
+                content_range,
+                content.len()
+            );
+            stream.write_all(response.as_bytes()).await.unwrap();
+            stream.write_all(content).await.unwrap();
+        }
+    });
+
+    format!("http://{}/dump.xml.7z", address)
+}
+
+#[tokio::test]
+async fn test_download_cached() {
+    const BODY: &[u8] = b"0123456789abcdefghij";
+
+    let url = spawn_test_server(BODY, "\"v1\"").await;
+    let client = reqwest::Client::new();
+    let temp_dir = tempfile::tempdir().unwrap();
+    let path = temp_dir.path().join("cache").join("dump.xml.7z");
+
+    let status = download_cached(&client, &url, &path).await.unwrap();
+    assert_eq!(status, DownloadStatus::Downloaded);
+    assert_eq!(std::fs::read(&path).unwrap(), BODY);
+
+    let status = download_cached(&client, &url, &path).await.unwrap();
+    assert_eq!(status, DownloadStatus::NotModified);
+
+    std::fs::remove_file(&path).unwrap();
+    std::fs::write(with_suffix(&path, ".part"), &BODY[..8]).unwrap();
+    std::fs::write(with_suffix(&path, ".part.meta"), "ETag: \"v1\"\n").unwrap();
+    let status = download_cached(&client, &url, &path).await.unwrap();
+    assert_eq!(status, DownloadStatus::Resumed);
+    assert_eq!(std::fs::read(&path).unwrap(), BODY);
+
+    std::fs::write(with_suffix(&path, ".part"), b"stale").unwrap();
+    std::fs::write(with_suffix(&path, ".part.meta"), "ETag: \"v0\"\n").unwrap();
+    let status = download_cached(&client, &url, &path).await.unwrap();
+    assert_eq!(status, DownloadStatus::Downloaded);
+    assert_eq!(std::fs::read(&path).unwrap(), BODY);
+
+    // 検証用のヘッダーが保存されていない場合は続きからダウンロードしない
+    std::fs::remove_file(&path).unwrap();
+    std::fs::write(with_suffix(&path, ".part"), b"stale").unwrap();
+    std::fs::remove_file(with_suffix(&path, ".part.meta")).ok();
+    let status = download_cached(&client, &url, &path).await.unwrap();
+    assert_eq!(status, DownloadStatus::Downloaded);
+    assert_eq!(std::fs::read(&path).unwrap(), BODY);
+}
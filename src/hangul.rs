@@ -0,0 +1,30 @@
+/// 初声 (ㄱ, ㄲ, ㄴ, ...) を互換字母で並べたもの
+const CHOSEONG: [char; 19] = [
+    'ㄱ', 'ㄲ', 'ㄴ', 'ㄷ', 'ㄸ', 'ㄹ', 'ㅁ', 'ㅂ', 'ㅃ', 'ㅅ', 'ㅆ', 'ㅇ', 'ㅈ', 'ㅉ', 'ㅊ', 'ㅋ',
+    'ㅌ', 'ㅍ', 'ㅎ',
+];
+
+/// ハングルの単語から各音節の初声を取り出した略語 (`리월` → `ㄹㅇ`) を作る。
+/// ハングルの音節が含まれない場合は`None`を返す。
+pub fn choseong_abbreviation(word: &str) -> Option<String> {
+    let abbreviation = word
+        .chars()
+        .filter_map(|c| match c {
+            '가'..='힣' => Some(CHOSEONG[(c as usize - '가' as usize) / 588]),
+            _ => None,
+        })
+        .collect::<String>();
+
+    if abbreviation.is_empty() {
+        None
+    } else {
+        Some(abbreviation)
+    }
+}
+
+#[test]
+fn test_choseong_abbreviation() {
+    assert_eq!(choseong_abbreviation("리월"), Some("ㄹㅇ".to_string()));
+    assert_eq!(choseong_abbreviation("몬드 성"), Some("ㅁㄷㅅ".to_string()));
+    assert_eq!(choseong_abbreviation("Paimon"), None);
+}
@@ -0,0 +1,271 @@
+use std::collections::HashSet;
+
+/// カタカナをひらがなに変換する。
+/// `・`と空白は取り除き、`ー`はそのまま残す。
+/// かな以外の文字 (漢字など) が含まれる場合は`None`を返す。
+pub fn katakana_to_hiragana(word: &str) -> Option<String> {
+    let mut hiragana = String::new();
+    for c in word.chars() {
+        match c {
+            // ァ..=ヶ (ヴ、ヵ、ヶを含む) はひらがなと同じ並び
+            'ァ'..='ヶ' => hiragana.push(char::from_u32(c as u32 - 0x60)?),
+            // 対応するひらがながないもの
+            'ヷ' => hiragana.push_str("ゔぁ"),
+            'ヸ' => hiragana.push_str("ゔぃ"),
+            'ヹ' => hiragana.push_str("ゔぇ"),
+            'ヺ' => hiragana.push_str("ゔぉ"),
+            'ぁ'..='ゖ' | 'ー' => hiragana.push(c),
+            '・' => {}
+            c if c.is_whitespace() => {}
+            _ => return None,
+        }
+    }
+
+    if hiragana.is_empty() {
+        None
+    } else {
+        Some(hiragana)
+    }
+}
+
+/// 単語のかなの部分で`お`段の長音をどう書いているか (`お`、`う`、`ー`) を先頭から順に返す。
+///
+/// `オオカミ`は`['お']`、`九条`のようにかなのない単語は空になる。
+pub fn long_o_spellings(word: &str) -> Vec<char> {
+    let mut spellings = Vec::new();
+    let mut previous = None;
+    for c in word.chars() {
+        let kana = katakana_to_hiragana(&c.to_string()).and_then(|kana| kana.chars().last());
+        if let Some(kana) = kana {
+            if previous.and_then(vowel) == Some('お') && matches!(kana, 'お' | 'う' | 'ー') {
+                spellings.push(kana);
+            }
+        }
+        previous = kana;
+    }
+    spellings
+}
+
+/// 単語に含まれるかなと、ローマ字から作った読み (`ー`を含まないひらがな) を先頭から対応付ける。
+///
+/// 単語のかなの部分は書かれている通りの読み (`ー`、小書きのかな、`っ`を含む) になり、
+/// 漢字などの部分にはローマ字の読みの対応する範囲が使われる。
+/// 対応付けられない場合は`None`を返す。
+pub fn align_reading(word: &str, reading: &str) -> Option<String> {
+    let mut tokens = Vec::new();
+    for c in word.chars() {
+        match katakana_to_hiragana(&c.to_string()) {
+            Some(kana) => tokens.extend(kana.chars().map(Token::Kana)),
+            None if c == '・' || c.is_whitespace() => {}
+            None => {
+                if tokens.last() != Some(&Token::Other) {
+                    tokens.push(Token::Other);
+                }
+            }
+        }
+    }
+
+    let mut aligner = Aligner {
+        tokens: &tokens,
+        reading: &reading.chars().collect::<Vec<_>>(),
+        failed: HashSet::new(),
+    };
+    let mut output = String::new();
+    aligner.align(0, 0, &mut output).then_some(output)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Token {
+    Kana(char),
+    /// かな以外の文字の並び
+    Other,
+}
+
+struct Aligner<'a> {
+    tokens: &'a [Token],
+    reading: &'a [char],
+    /// 対応付けに失敗した状態 (トークンの位置、読みの位置、直前の母音)。
+    /// 同じ状態を何度も試すと単語の長さに対して指数的に遅くなるため記録しておく。
+    failed: HashSet<(usize, usize, Option<char>)>,
+}
+
+impl Aligner<'_> {
+    /// `tokens[token..]`と`reading[position..]`を対応付ける。
+    fn align(&mut self, token: usize, position: usize, output: &mut String) -> bool {
+        let previous = output.chars().last().and_then(vowel);
+        if self.failed.contains(&(token, position, previous)) {
+            return false;
+        }
+        let aligned = self.align_token(token, position, previous, output);
+        if !aligned {
+            self.failed.insert((token, position, previous));
+        }
+        aligned
+    }
+
+    fn align_token(
+        &mut self,
+        token: usize,
+        position: usize,
+        previous: Option<char>,
+        output: &mut String,
+    ) -> bool {
+        let Some(&current) = self.tokens.get(token) else {
+            return position == self.reading.len();
+        };
+        let next = self.reading.get(position).copied();
+
+        match current {
+            Token::Kana('ー') => {
+                // 直前の母音を伸ばした音か、ローマ字で長音が省略されている
+                let long = matches!((previous, next), (Some(previous), Some(next)) if is_long_vowel_of(previous, next));
+                (long && self.consume(token, position, 1, "ー", output))
+                    || self.consume(token, position, 0, "ー", output)
+            }
+            Token::Kana(kana) => {
+                let text = kana.to_string();
+                // 長音符号の`ō`は`おう`になるので、`おお`と書かれた単語の`お`にも対応付ける
+                let matches = next.map(normalize) == Some(normalize(kana))
+                    || (kana == 'お' && next == Some('う') && previous == Some('お'));
+                (matches && self.consume(token, position, 1, &text, output))
+                    // 小書きのかなと`っ`はローマ字で表されないことがある
+                    || (normalize(kana) != kana && self.consume(token, position, 0, &text, output))
+            }
+            Token::Other => (1..=self.reading.len() - position).any(|consumed| {
+                let text = self.reading[position..position + consumed]
+                    .iter()
+                    .collect::<String>();
+                self.consume(token, position, consumed, &text, output)
+            }),
+        }
+    }
+
+    /// 読みの`position`から`consumed`文字を`text`として出力し、次のトークンから対応付ける。
+    /// 失敗した場合は出力を元に戻す。
+    fn consume(
+        &mut self,
+        token: usize,
+        position: usize,
+        consumed: usize,
+        text: &str,
+        output: &mut String,
+    ) -> bool {
+        let len = output.len();
+        output.push_str(text);
+        if self.align(token + 1, position + consumed, output) {
+            return true;
+        }
+        output.truncate(len);
+        false
+    }
+}
+
+/// 小書きのかなを通常のかなにする。`ゔ`は`ぶ`として扱う。
+fn normalize(kana: char) -> char {
+    match kana {
+        'ぁ' => 'あ',
+        'ぃ' => 'い',
+        'ぅ' => 'う',
+        'ぇ' => 'え',
+        'ぉ' => 'お',
+        'っ' => 'つ',
+        'ゃ' => 'や',
+        'ゅ' => 'ゆ',
+        'ょ' => 'よ',
+        'ゎ' => 'わ',
+        'ゕ' => 'か',
+        'ゖ' => 'け',
+        'ゔ' => 'ぶ',
+        _ => kana,
+    }
+}
+
+/// かなの母音 (`あいうえお`のいずれか)
+fn vowel(kana: char) -> Option<char> {
+    const ROWS: &[(char, &str)] = &[
+        ('あ', "あかさたなはまやらわがざだばぱぁゃゎゕ"),
+        ('い', "いきしちにひみりゐぎじぢびぴぃ"),
+        ('う', "うくすつぬふむゆるぐずづぶぷぅゅゔ"),
+        ('え', "えけせてねへめれゑげぜでべぺぇゖ"),
+        ('お', "おこそとのほもよろをごぞどぼぽぉょ"),
+    ];
+    ROWS.iter()
+        .find(|(_, row)| row.contains(kana))
+        .map(|&(vowel, _)| vowel)
+}
+
+/// `next`が母音`previous`を伸ばした音として読めるか (`おう`、`えい`を含む)
+fn is_long_vowel_of(previous: char, next: char) -> bool {
+    next == previous || matches!((previous, next), ('お', 'う') | ('え', 'い'))
+}
+
+#[test]
+fn test_katakana_to_hiragana() {
+    assert_eq!(
+        katakana_to_hiragana("ウェンティ"),
+        Some("うぇんてぃ".to_string())
+    );
+    assert_eq!(katakana_to_hiragana("ユーラ"), Some("ゆーら".to_string()));
+    assert_eq!(
+        katakana_to_hiragana("ヴァネッサ・ヰ"),
+        Some("ゔぁねっさゐ".to_string())
+    );
+    assert_eq!(katakana_to_hiragana("ヷルカ"), Some("ゔぁるか".to_string()));
+    assert_eq!(katakana_to_hiragana("璃月港"), None);
+    assert_eq!(katakana_to_hiragana("・"), None);
+}
+
+#[test]
+fn test_align_reading() {
+    assert_eq!(
+        align_reading("ユーラ", "ゆうら"),
+        Some("ゆーら".to_string())
+    );
+    assert_eq!(align_reading("ユーラ", "ゆら"), Some("ゆーら".to_string()));
+    assert_eq!(
+        align_reading("モンド城", "もんどじょう"),
+        Some("もんどじょう".to_string())
+    );
+    assert_eq!(
+        align_reading("スメールシティ", "すめえるしてぃ"),
+        Some("すめーるしてぃ".to_string())
+    );
+    assert_eq!(
+        align_reading("ベッドタウン", "べどたうん"),
+        Some("べっどたうん".to_string())
+    );
+    assert_eq!(
+        align_reading("璃月ハーバー", "りいゆえはあばあ"),
+        Some("りいゆえはーばー".to_string())
+    );
+    assert_eq!(
+        align_reading("大オオカミ", "だいおうかみ"),
+        Some("だいおおかみ".to_string())
+    );
+    assert_eq!(align_reading("モンド", "すめえる"), None);
+}
+
+#[test]
+fn test_align_reading_long_word() {
+    // 漢字とかなが交互に並ぶ長い単語でも、対応付けに失敗する場合に時間がかからない
+    let word = "月ア".repeat(12) + "ン";
+    let start = std::time::Instant::now();
+    assert_eq!(align_reading(&word, &"あ".repeat(40)), None);
+    assert!(start.elapsed() < std::time::Duration::from_secs(1));
+
+    assert_eq!(
+        align_reading(
+            "璃月ハーバー稲妻ナルカミ須弥スメール",
+            "りいゆえはあばあいなずまなるかみしゅみすめえる"
+        ),
+        Some("りいゆえはーばーいなずまなるかみしゅみすめーる".to_string())
+    );
+}
+
+#[test]
+fn test_long_o_spellings() {
+    assert_eq!(long_o_spellings("トオル"), vec!['お']);
+    assert_eq!(long_o_spellings("ユウキ・コウ"), vec!['う']);
+    assert_eq!(long_o_spellings("ロード"), vec!['ー']);
+    assert_eq!(long_o_spellings("九条裟羅"), Vec::<char>::new());
+}
@@ -1,141 +1,443 @@
-use std::path::Path;
+use std::{
+    collections::HashSet,
+    io::{BufRead, BufWriter, Write},
+    path::Path,
+    str::FromStr,
+};
 
 use anyhow::Context;
-use quick_xml::events::Event;
-use romaji::romaji_to_hiragana;
-use tokio::{fs::File, io::AsyncWriteExt};
+use archive::open_database_dump;
+use clap::Parser;
+use cli::{Cli, Command, GenerateArgs, LookupArgs, PosMode, SourceArgs, StatsArgs, WikiArgs};
+use dedupe::{deduplicate, ReadingCollision};
+use download::{cache_dir, download_cached, DownloadStatus};
+use hangul::choseong_abbreviation;
+use kana::{align_reading, katakana_to_hiragana};
+use overrides::Overrides;
+use pinyin::pinyin_to_syllables;
+use profile::WikiProfile;
+use quick_xml::events::{BytesText, Event};
+use romaji::{romaji_to_hiragana, romaji_to_hiragana_strict, RomajiMode};
 
 use crate::{
+    classify::{classify, classify_infobox, WordCategory},
     ext::NodeExt,
-    mediawiki::{parse_mediawiki, Node, TemplateArgument},
+    mediawiki::{parse_categories, parse_mediawiki, split_sections, Node, TemplateArgument},
 };
 
+mod archive;
+mod classify;
+mod cli;
+mod dedupe;
+mod download;
 mod ext;
+mod hangul;
+mod kana;
 mod mediawiki;
+mod overrides;
+mod pinyin;
+mod profile;
 mod romaji;
-
-const DATABASE_DUMP_URL: &str =
-    "https://s3.amazonaws.com/wikia_xml_dumps/g/ge/gensinimpact_pages_current.xml.7z";
-const DATABASE_DUMP_FILENAME: &str = "gensinimpact_pages_current.xml";
+mod writer;
 
 #[tokio::main]
 async fn main() -> anyhow::Result<()> {
-    let database_dump = if let Some(filename) = std::env::args().nth(1) {
-        std::fs::read(filename).context("Failed to read file")?
-    } else {
-        let temp_dir = tempfile::tempdir().context("Failed to create tempdir")?;
+    let cli = Cli::parse();
+
+    env_logger::Builder::new()
+        .filter_level(cli.log_level())
+        .format_timestamp(None)
+        .format_target(false)
+        .init();
+
+    match cli.command {
+        Some(Command::Generate(args)) => generate(args).await,
+        Some(Command::Download(args)) => download(args).await,
+        Some(Command::Stats(args)) => stats(args).await,
+        Some(Command::Lookup(args)) => lookup(args).await,
+        None => generate(GenerateArgs::default()).await,
+    }
+}
+
+async fn generate(args: GenerateArgs) -> anyhow::Result<()> {
+    let mut dictionary = load_dictionary(&args.source, args.format.language()).await?;
+
+    if args.pos_mode == PosMode::ProperNoun {
+        for entry in &mut dictionary.entries {
+            entry.category = WordCategory::ProperNoun;
+        }
+    }
+
+    let mut output: Box<dyn Write> = match &args.output {
+        Some(path) => Box::new(BufWriter::new(
+            std::fs::File::create(path)
+                .with_context(|| format!("Failed to create {}", path.display()))?,
+        )),
+        None => Box::new(BufWriter::new(std::io::stdout().lock())),
+    };
+    args.format
+        .writer()
+        .write(&dictionary, &mut output)
+        .context("Failed to write dictionary")?;
+    output.flush().context("Failed to write dictionary")?;
+
+    log::info!("Wrote {} entries", dictionary.entries.len());
+
+    Ok(())
+}
+
+async fn download(args: WikiArgs) -> anyhow::Result<()> {
+    let profile = args.profile()?;
+    let path = cache_dir()?.join(profile.archive_filename());
+    download_compressed_database_dump(&profile.dump_url, &path).await?;
+    println!("{}", path.display());
+    Ok(())
+}
+
+async fn stats(args: StatsArgs) -> anyhow::Result<()> {
+    let dictionary = load_dictionary(&args.source, args.language).await?;
+
+    let words = dictionary
+        .entries
+        .iter()
+        .map(|entry| &entry.word)
+        .collect::<HashSet<_>>();
+    let pages = dictionary
+        .entries
+        .iter()
+        .flat_map(|entry| &entry.page_titles)
+        .collect::<HashSet<_>>();
+
+    println!(
+        "Timestamp: {}",
+        dictionary.timestamp.as_deref().unwrap_or("-")
+    );
+    println!("Entries: {}", dictionary.entries.len());
+    println!("Unique words: {}", words.len());
+    println!("Source pages: {}", pages.len());
+    for category in [
+        WordCategory::Person,
+        WordCategory::Place,
+        WordCategory::Organization,
+        WordCategory::Noun,
+        WordCategory::ProperNoun,
+    ] {
+        let count = dictionary
+            .entries
+            .iter()
+            .filter(|entry| entry.category == category)
+            .count();
+        println!("{:?}: {}", category, count);
+    }
+
+    Ok(())
+}
+
+async fn lookup(args: LookupArgs) -> anyhow::Result<()> {
+    let dictionary = load_dictionary(&args.source, args.language).await?;
+
+    let mut found = false;
+    for entry in &dictionary.entries {
+        if entry.word == args.query || entry.yomi == args.query {
+            println!(
+                "{}\t{}\t{:?}\t{}",
+                entry.yomi,
+                entry.word,
+                entry.category,
+                entry.page_titles.join(", ")
+            );
+            found = true;
+        }
+    }
 
-        eprintln!("Downloading database dump...");
+    if !found {
+        anyhow::bail!("No entries found for {}", args.query);
+    }
 
-        let compressed_database_dump_filename =
-            temp_dir.path().join("gensinimpact_pages_current.xml.7z");
-        download_compressed_database_dump(&compressed_database_dump_filename).await?;
+    Ok(())
+}
 
-        eprintln!("Decompressing database dump...");
+/// データベースダンプを読み込み、指定した言語の辞書を作成する。
+async fn load_dictionary(source: &SourceArgs, language: Language) -> anyhow::Result<Dictionary> {
+    let profile = source.wiki.profile()?;
 
-        decompress_file(&compressed_database_dump_filename, DATABASE_DUMP_FILENAME)
-            .await
+    let database_dump: Box<dyn BufRead> = if let Some(filename) = &source.input {
+        open_database_dump(filename, &profile.archive_member).context("Failed to open file")?
+    } else {
+        log::info!("Downloading database dump...");
+
+        let compressed_database_dump_filename = cache_dir()?.join(profile.archive_filename());
+        download_compressed_database_dump(&profile.dump_url, &compressed_database_dump_filename)
+            .await?;
+
+        log::info!("Decompressing database dump...");
+
+        open_database_dump(&compressed_database_dump_filename, &profile.archive_member)
             .context("Failed to decompress file")?
     };
 
-    eprintln!("Generating dictionary...");
+    log::info!("Generating dictionary...");
 
-    let pages_iter = parse_database_dump(&database_dump);
+    let pages_iter = parse_database_dump(database_dump, source.filter(), profile);
 
+    let mut dictionary = Dictionary::default();
     for page in pages_iter {
         let page = page.context("Failed to parse database dump")?;
-        let entries = to_ime_dictionary_entry(&page);
-        for entry in entries {
-            println!("{}\t{}\t固有名詞", entry.yomi, entry.word);
+        dictionary.update_timestamp(&page.revision.timestamp);
+        dictionary.entries.extend(to_ime_dictionary_entry(
+            &page,
+            language,
+            source.strict_romaji,
+            source.romaji_mode,
+        ));
+    }
+
+    let mut overrides = Vec::new();
+    if language == Language::Japanese {
+        overrides.push(Overrides::japanese());
+    }
+    let builtin_overrides = overrides.len();
+    if let Some(path) = &source.overrides {
+        overrides.push(Overrides::load(path)?);
+    }
+
+    let (unused, collisions) = apply_overrides(&mut dictionary, &overrides);
+    for (i, unused) in unused.into_iter().enumerate() {
+        for unused in unused {
+            // 組み込みの修正は辞書を生成するWikiによっては当てはまらない
+            if i < builtin_overrides {
+                log::debug!("Built-in override does not match any entry: {}", unused);
+            } else {
+                log::warn!("Override does not match any entry: {}", unused);
+            }
         }
     }
+    for collision in collisions {
+        log::warn!(
+            "{} has different readings: {}",
+            collision.word,
+            collision.yomis.join(", ")
+        );
+    }
 
-    Ok(())
+    Ok(dictionary)
 }
 
-/// Genshin Impact Wikiからデータベースをダウンロードし指定したファイルに保存する。
-async fn download_compressed_database_dump(filename: &Path) -> anyhow::Result<()> {
-    let mut response = reqwest::get(DATABASE_DUMP_URL).await.unwrap();
-    let mut file = File::create(filename)
-        .await
-        .context("Failed to create temp file")?;
-    while let Some(chunk) = response.chunk().await.unwrap() {
-        file.write_all(&chunk)
-            .await
-            .context("Failed to write chunk to tempfile")?;
+/// 手動の修正を順に適用し、単語と読みが同じエントリをまとめる。
+///
+/// 読みの衝突は読みや単語を追加する修正を適用する前に調べるため、
+/// `extra_yomi`で追加した読みは衝突として報告されない。
+/// 返り値の1つ目は、修正ごとのどの単語にも当てはまらなかった修正の一覧。
+fn apply_overrides(
+    dictionary: &mut Dictionary,
+    overrides: &[Overrides],
+) -> (Vec<Vec<String>>, Vec<ReadingCollision>) {
+    let mut unused = overrides
+        .iter()
+        .map(|overrides| overrides.apply_replacements(dictionary))
+        .collect::<Vec<_>>();
+
+    let (entries, collisions) = deduplicate(std::mem::take(&mut dictionary.entries));
+    dictionary.entries = entries;
+
+    for (overrides, unused) in overrides.iter().zip(&mut unused) {
+        unused.extend(overrides.apply_additions(dictionary));
     }
-    Ok(())
+    let (entries, _) = deduplicate(std::mem::take(&mut dictionary.entries));
+    dictionary.entries = entries;
+
+    (unused, collisions)
 }
 
-/// 指定したアーカイブファイルから指定したファイルを取り出す。
-/// 返り値はファイルのバイト列
-async fn decompress_file(archive_file: &Path, filename: &str) -> anyhow::Result<Vec<u8>> {
-    let command = tokio::process::Command::new("7z")
-        .arg("x")
-        .arg("-so")
-        .arg(archive_file)
-        .arg(filename)
-        .stdout(std::process::Stdio::piped())
-        .spawn()
-        .context("Failed to spawn 7z")?;
-
-    let output = command
-        .wait_with_output()
-        .await
-        .context("Failed to wait for 7z")?;
+/// TOML形式のファイルを読み込む。
+fn load_toml<T: serde::de::DeserializeOwned>(path: &Path) -> anyhow::Result<T> {
+    let text = std::fs::read_to_string(path)
+        .with_context(|| format!("Failed to read {}", path.display()))?;
+    toml::from_str(&text).with_context(|| format!("Failed to parse {}", path.display()))
+}
 
-    if !output.status.success() {
-        anyhow::bail!("7z exited with non-zero status code");
-    }
+/// Wikiからデータベースをダウンロードし指定したファイルに保存する。
+/// 保存済みのファイルが最新の場合はダウンロードしない。
+async fn download_compressed_database_dump(url: &str, filename: &Path) -> anyhow::Result<()> {
+    log::debug!("Downloading {} to {}", url, filename.display());
 
-    Ok(output.stdout)
+    let client = reqwest::Client::new();
+    let status = download_cached(&client, url, filename)
+        .await
+        .context("Failed to download database dump")?;
+    match status {
+        DownloadStatus::NotModified => log::info!("Database dump is up to date"),
+        DownloadStatus::Downloaded => log::debug!("Downloaded database dump"),
+        DownloadStatus::Resumed => log::debug!("Resumed downloading database dump"),
+    }
+    Ok(())
 }
 
 #[derive(Debug, Default)]
 struct Page {
+    title: String,
+    /// 名前空間の番号 (0: 標準、2: User、10: Templateなど)
+    namespace: i32,
+    id: u64,
+    /// リダイレクトページかどうか
+    redirect: bool,
     revision: Revision,
 }
 
 #[derive(Debug, Default)]
 struct Revision {
+    timestamp: String,
     text: PageText,
 }
 
 #[derive(Debug, Default)]
 struct PageText {
     other_languages: Vec<OtherLanguages>,
+    /// ページで使われているインフォボックスのテンプレート名
+    infoboxes: Vec<String>,
+    categories: Vec<String>,
 }
 
 #[derive(Debug, Default)]
 struct OtherLanguages {
     ja: Option<Vec<Node<'static>>>,
     ja_rm: Option<Vec<Node<'static>>>,
+    zhs: Option<Vec<Node<'static>>>,
+    zhs_rm: Option<Vec<Node<'static>>>,
+    zht: Option<Vec<Node<'static>>>,
+    zht_rm: Option<Vec<Node<'static>>>,
+    ko: Option<Vec<Node<'static>>>,
+    ko_rm: Option<Vec<Node<'static>>>,
+    /// テンプレートと同じ節にあるインフォボックスの名前 (前にあるものを優先する)
+    infobox: Option<String>,
+}
+
+/// データベースダンプから読み込むページの条件
+#[derive(Debug, Clone)]
+struct PageFilter {
+    /// 読み込む名前空間
+    namespaces: Vec<i32>,
+    /// リダイレクトページも読み込むかどうか
+    include_redirects: bool,
+}
+
+impl Default for PageFilter {
+    fn default() -> Self {
+        PageFilter {
+            namespaces: vec![0],
+            include_redirects: false,
+        }
+    }
+}
+
+impl PageFilter {
+    fn matches(&self, page: &Page) -> bool {
+        self.namespaces.contains(&page.namespace) && (self.include_redirects || !page.redirect)
+    }
 }
 
-fn parse_database_dump(input: &[u8]) -> impl Iterator<Item = anyhow::Result<Page>> + '_ {
+fn parse_database_dump<R: BufRead>(
+    input: R,
+    filter: PageFilter,
+    profile: WikiProfile,
+) -> impl Iterator<Item = anyhow::Result<Page>> {
     let reader = quick_xml::Reader::from_reader(input);
 
-    struct PagesIter<'a> {
-        reader: quick_xml::Reader<&'a [u8]>,
+    struct PagesIter<R> {
+        reader: quick_xml::Reader<R>,
+        buf: Vec<u8>,
+        filter: PageFilter,
+        profile: WikiProfile,
     }
 
-    impl<'a> Iterator for PagesIter<'a> {
+    impl<R: BufRead> Iterator for PagesIter<R> {
         type Item = anyhow::Result<Page>;
         fn next(&mut self) -> Option<Self::Item> {
+            loop {
+                match self.read_page()? {
+                    Ok(page) if !self.filter.matches(&page) => continue,
+                    page => return Some(page),
+                }
+            }
+        }
+    }
+
+    impl<R: BufRead> PagesIter<R> {
+        fn read_page(&mut self) -> Option<anyhow::Result<Page>> {
             let mut in_page_tag = false;
+            let mut in_title_tag = false;
+            let mut in_ns_tag = false;
+            let mut in_id_tag = false;
             let mut in_revision_tag = false;
+            let mut in_timestamp_tag = false;
             let mut in_text_tag = false;
 
             let mut page = Page::default();
 
             loop {
-                match self.reader.read_event() {
+                self.buf.clear();
+                match self.reader.read_event_into(&mut self.buf) {
                     Ok(Event::Start(ref e)) if e.name().0 == b"page" => {
                         in_page_tag = true;
                     }
+                    Ok(Event::Start(ref e)) if in_page_tag && e.name().0 == b"title" => {
+                        in_title_tag = true;
+                    }
+                    Ok(Event::Text(ref e)) if in_title_tag => {
+                        page.title = match e.unescape() {
+                            Ok(text) => text.into_owned(),
+                            Err(e) => return Some(Err(e.into())),
+                        };
+                    }
+                    Ok(Event::End(ref e)) if in_title_tag && e.name().0 == b"title" => {
+                        in_title_tag = false;
+                    }
+                    Ok(Event::Start(ref e)) if in_page_tag && e.name().0 == b"ns" => {
+                        in_ns_tag = true;
+                    }
+                    Ok(Event::Text(ref e)) if in_ns_tag => {
+                        page.namespace = match parse_text(e) {
+                            Ok(namespace) => namespace,
+                            Err(e) => return Some(Err(e)),
+                        };
+                    }
+                    Ok(Event::End(ref e)) if in_ns_tag && e.name().0 == b"ns" => {
+                        in_ns_tag = false;
+                    }
+                    Ok(Event::Start(ref e))
+                        if in_page_tag && !in_revision_tag && e.name().0 == b"id" =>
+                    {
+                        in_id_tag = true;
+                    }
+                    Ok(Event::Text(ref e)) if in_id_tag => {
+                        page.id = match parse_text(e) {
+                            Ok(id) => id,
+                            Err(e) => return Some(Err(e)),
+                        };
+                    }
+                    Ok(Event::End(ref e)) if in_id_tag && e.name().0 == b"id" => {
+                        in_id_tag = false;
+                    }
+                    Ok(Event::Empty(ref e)) if in_page_tag && e.name().0 == b"redirect" => {
+                        page.redirect = true;
+                    }
                     Ok(Event::Start(ref e)) if in_page_tag && e.name().0 == b"revision" => {
                         in_revision_tag = true;
                     }
+                    Ok(Event::Start(ref e))
+                        if in_page_tag && in_revision_tag && e.name().0 == b"timestamp" =>
+                    {
+                        in_timestamp_tag = true;
+                    }
+                    Ok(Event::Text(ref e)) if in_timestamp_tag => {
+                        page.revision.timestamp = match e.unescape() {
+                            Ok(text) => text.into_owned(),
+                            Err(e) => return Some(Err(e.into())),
+                        };
+                    }
+                    Ok(Event::End(ref e)) if in_timestamp_tag && e.name().0 == b"timestamp" => {
+                        in_timestamp_tag = false;
+                    }
                     Ok(Event::Start(ref e))
                         if in_page_tag && in_revision_tag && e.name().0 == b"text" =>
                     {
@@ -147,36 +449,71 @@ fn parse_database_dump(input: &[u8]) -> impl Iterator<Item = anyhow::Result<Page
                             Err(e) => return Some(Err(e.into())),
                         };
 
-                        if !text.contains("Other Languages") {
-                            continue;
+                        if is_redirect(&text) {
+                            page.redirect = true;
                         }
 
-                        let document = parse_mediawiki(&text);
-
-                        document.traverse(&mut |elem| {
-                            if let Node::Template { name, arguments } = elem {
-                                if *name != "Other Languages" {
-                                    return;
-                                }
+                        page.revision.text.categories = parse_categories(&text)
+                            .map(|category| category.to_string())
+                            .collect();
 
-                                let mut other_languages = OtherLanguages::default();
+                        if !text.contains(&self.profile.template_name) {
+                            continue;
+                        }
 
-                                if let Some(arg) =
-                                    arguments.iter().find(|arg| arg.name == Some("ja".into()))
-                                {
-                                    other_languages.ja = Some(arg.value.to_static());
+                        for section in split_sections(&text) {
+                            let first = page.revision.text.other_languages.len();
+                            // 節の中のインフォボックスと、それより前にあるテンプレートの数
+                            let mut infoboxes = Vec::<(usize, String)>::new();
+
+                            parse_mediawiki(section).traverse(&mut |elem| {
+                                if let Node::Template { name, arguments } = elem {
+                                    if name.ends_with("Infobox") {
+                                        page.revision.text.infoboxes.push(name.to_string());
+                                        infoboxes.push((
+                                            page.revision.text.other_languages.len(),
+                                            name.to_string(),
+                                        ));
+                                    }
+
+                                    if *name != self.profile.template_name {
+                                        return;
+                                    }
+
+                                    let argument = |key: &str| {
+                                        arguments
+                                            .iter()
+                                            .find(|arg| arg.name == Some(key.into()))
+                                            .map(|arg| arg.value.to_static())
+                                    };
+
+                                    let keys = &self.profile.arguments;
+                                    let other_languages = OtherLanguages {
+                                        ja: argument(&keys.ja),
+                                        ja_rm: argument(&keys.ja_rm),
+                                        zhs: argument(&keys.zhs),
+                                        zhs_rm: argument(&keys.zhs_rm),
+                                        zht: argument(&keys.zht),
+                                        zht_rm: argument(&keys.zht_rm),
+                                        ko: argument(&keys.ko),
+                                        ko_rm: argument(&keys.ko_rm),
+                                        infobox: None,
+                                    };
+
+                                    page.revision.text.other_languages.push(other_languages);
                                 }
+                            });
 
-                                if let Some(arg) = arguments
+                            let templates = &mut page.revision.text.other_languages;
+                            for (index, template) in templates.iter_mut().enumerate().skip(first) {
+                                template.infobox = infoboxes
                                     .iter()
-                                    .find(|arg| arg.name == Some("ja_rm".into()))
-                                {
-                                    other_languages.ja_rm = Some(arg.value.to_static());
-                                }
-
-                                page.revision.text.other_languages.push(other_languages);
+                                    .rev()
+                                    .find(|&&(count, _)| count <= index)
+                                    .or(infoboxes.first())
+                                    .map(|(_, name)| name.clone());
                             }
-                        });
+                        }
                     }
                     Ok(Event::End(ref e))
                         if in_page_tag
@@ -189,7 +526,7 @@ fn parse_database_dump(input: &[u8]) -> impl Iterator<Item = anyhow::Result<Page
                     Ok(Event::End(ref e))
                         if in_page_tag && in_revision_tag && e.name().0 == b"revision" =>
                     {
-                        in_text_tag = false;
+                        in_revision_tag = false;
                     }
                     Ok(Event::End(ref e)) if in_page_tag && e.name().0 == b"page" => {
                         return Some(Ok(page));
@@ -202,42 +539,213 @@ fn parse_database_dump(input: &[u8]) -> impl Iterator<Item = anyhow::Result<Page
         }
     }
 
-    PagesIter { reader }
+    PagesIter {
+        reader,
+        buf: Vec::new(),
+        filter,
+        profile,
+    }
+}
+
+/// `#REDIRECT [[...]]`で始まるページかどうか
+fn is_redirect(text: &str) -> bool {
+    text.trim_start()
+        .get(..9)
+        .is_some_and(|s| s.eq_ignore_ascii_case("#redirect"))
+}
+
+fn parse_text<T>(text: &BytesText) -> anyhow::Result<T>
+where
+    T: FromStr,
+    T::Err: std::error::Error + Send + Sync + 'static,
+{
+    Ok(text.unescape()?.trim().parse()?)
+}
+
+#[derive(Debug, Default)]
+struct Dictionary {
+    entries: Vec<IMEDictionaryEntry>,
+    /// データベースダンプに含まれる最も新しい版の日時
+    timestamp: Option<String>,
+}
+
+impl Dictionary {
+    fn update_timestamp(&mut self, timestamp: &str) {
+        if timestamp.is_empty() {
+            return;
+        }
+        // ISO 8601形式なので文字列として比較できる
+        if self.timestamp.as_deref().is_none_or(|t| t < timestamp) {
+            self.timestamp = Some(timestamp.to_string());
+        }
+    }
 }
 
 #[derive(Debug)]
 struct IMEDictionaryEntry {
     word: String,
+    /// 読み。中国語の場合は空白区切りの拼音、韓国語の場合は初声の略語
     yomi: String,
+    /// 単語の出典となったページのタイトル
+    page_titles: Vec<String>,
+    category: WordCategory,
+    /// 韓国語の単語のローマ字表記
+    romanization: Option<String>,
+}
+
+/// テスト用のエントリ (分類は固有名詞)
+#[cfg(test)]
+fn test_entry(word: &str, yomi: &str, page_title: &str) -> IMEDictionaryEntry {
+    IMEDictionaryEntry {
+        word: word.to_string(),
+        yomi: yomi.to_string(),
+        page_titles: vec![page_title.to_string()],
+        category: WordCategory::ProperNoun,
+        romanization: None,
+    }
 }
 
-fn to_ime_dictionary_entry(page: &Page) -> impl Iterator<Item = IMEDictionaryEntry> + '_ {
+/// テスト用の辞書 (タイムスタンプなし)
+#[cfg(test)]
+fn test_dictionary(entries: Vec<IMEDictionaryEntry>) -> Dictionary {
+    Dictionary {
+        entries,
+        ..Default::default()
+    }
+}
+
+/// 辞書を生成する言語
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, clap::ValueEnum)]
+enum Language {
+    #[default]
+    #[value(name = "ja")]
+    Japanese,
+    /// 簡体字中国語。読みは拼音
+    #[value(name = "zh-hans")]
+    ChineseSimplified,
+    /// 繁体字中国語。読みは拼音
+    #[value(name = "zh-hant")]
+    ChineseTraditional,
+    /// 韓国語。読みは初声の略語
+    #[value(name = "ko")]
+    Korean,
+}
+
+/// `strict_romaji`が`true`の場合、読みをローマ字から作る単語のうち、
+/// ローマ字に変換できない文字があるものを含めない。
+/// `romaji_mode`は`ti`、`tu`などのつづりの読み方。
+fn to_ime_dictionary_entry(
+    page: &Page,
+    language: Language,
+    strict_romaji: bool,
+    romaji_mode: RomajiMode,
+) -> impl Iterator<Item = IMEDictionaryEntry> + '_ {
+    let page_category = classify(page);
+
     page.revision.text.other_languages.iter().flat_map(
-        |other_languages| -> Option<IMEDictionaryEntry> {
-            fn template(name: &str, arguments: &[TemplateArgument], output: &mut String) {
+        move |other_languages| -> Option<IMEDictionaryEntry> {
+            /// `{{Rubi|漢字|かんじ}}`の親文字だけを残す
+            fn base_text(name: &str, arguments: &[TemplateArgument], output: &mut String) {
                 if name == "Rubi" {
                     for i in (0..arguments.len()).step_by(2) {
-                        output.push_str(&arguments[i].value.to_string(template));
+                        output.push_str(&arguments[i].value.to_string(base_text));
                     }
                 }
             }
 
-            let ja = other_languages.ja.as_ref()?.to_string(template);
-            let ja_rm = other_languages.ja_rm.as_ref()?.to_string(template);
-
-            let word = remove_tags(&ja).trim().to_string();
-            let romaji = remove_tags(&ja_rm).trim();
-
-            if word.is_empty() || romaji.is_empty() {
-                return None;
+            /// `{{Rubi|漢字|かんじ}}`のルビだけを残す
+            fn ruby_text(name: &str, arguments: &[TemplateArgument], output: &mut String) {
+                if name == "Rubi" {
+                    // ルビが省略されている場合は親文字を使う
+                    for pair in arguments.chunks(2) {
+                        output.push_str(&pair[pair.len() - 1].value.to_string(ruby_text));
+                    }
+                }
             }
 
-            let yomi =
-                romaji_to_hiragana(romaji, word.contains('ー')).replace("りいうぇ", "りーゆえ");
+            let text = |nodes: &Option<Vec<Node>>,
+                        template: fn(&str, &[TemplateArgument], &mut String)|
+             -> Option<String> {
+                let text = nodes.as_ref()?.to_string(template);
+                let text = remove_tags(&text).trim();
+                (!text.is_empty()).then(|| text.to_string())
+            };
+
+            let mut romanization = None;
+            let (word, yomi) = match language {
+                Language::Japanese => {
+                    let word = text(&other_languages.ja, base_text)?;
+                    let romaji_yomi = || -> Option<String> {
+                        let romaji = text(&other_languages.ja_rm, base_text)?;
+                        let hiragana =
+                            match romaji_to_hiragana_strict(&romaji, &word, false, romaji_mode) {
+                                Ok(hiragana) => hiragana,
+                                Err(error) => {
+                                    log::warn!("{}: {} in romaji {:?}", page.title, error, romaji);
+                                    if strict_romaji {
+                                        return None;
+                                    }
+                                    romaji_to_hiragana(&romaji, &word, false, romaji_mode)
+                                }
+                            };
+                        // 単語のかなの部分に合わせて`ー`や小書きのかなを補う
+                        let yomi = align_reading(&word, &hiragana).unwrap_or_else(|| {
+                            romaji_to_hiragana(&romaji, &word, word.contains('ー'), romaji_mode)
+                        });
+                        Some(yomi)
+                    };
+                    // かなだけの単語はローマ字を使わずにそのまま読みにする
+                    let yomi = if let Some(yomi) = katakana_to_hiragana(&word) {
+                        yomi
+                    } else if let Some(yomi) = text(&other_languages.ja, ruby_text)
+                        .and_then(|furigana| katakana_to_hiragana(&furigana))
+                    {
+                        // ルビがあればローマ字より優先する
+                        if let Some(romaji_yomi) = romaji_yomi().filter(|r| r != &yomi) {
+                            log::warn!(
+                                "{}: reading from ruby ({}) differs from romaji ({})",
+                                word,
+                                yomi,
+                                romaji_yomi
+                            );
+                        }
+                        yomi
+                    } else {
+                        romaji_yomi()?
+                    };
+                    (word, yomi)
+                }
+                Language::ChineseSimplified => {
+                    let word = text(&other_languages.zhs, base_text)?;
+                    let pinyin = text(&other_languages.zhs_rm, base_text)?;
+                    (word, pinyin_to_syllables(&pinyin)?.join(" "))
+                }
+                Language::ChineseTraditional => {
+                    let word = text(&other_languages.zht, base_text)?;
+                    let pinyin = text(&other_languages.zht_rm, base_text)?;
+                    (word, pinyin_to_syllables(&pinyin)?.join(" "))
+                }
+                Language::Korean => {
+                    let word = text(&other_languages.ko, base_text)?;
+                    let yomi = choseong_abbreviation(&word)?;
+                    romanization = text(&other_languages.ko_rm, base_text);
+                    (word, yomi)
+                }
+            };
+
+            // 同じ節のインフォボックスで分類できなければページ全体から分類する
+            let category = other_languages
+                .infobox
+                .as_deref()
+                .and_then(classify_infobox)
+                .unwrap_or(page_category);
 
             Some(IMEDictionaryEntry {
-                word: word.to_string(),
+                word,
                 yomi,
+                page_titles: vec![page.title.clone()],
+                category,
+                romanization,
             })
         },
     )
@@ -253,3 +761,253 @@ fn remove_tags(s: &str) -> &str {
     }
     s
 }
+
+#[test]
+fn test_parse_database_dump() {
+    let input = r#"<mediawiki>
+<page>
+<title>Liyue</title>
+<ns>0</ns>
+<id>100</id>
+<revision>
+<id>5</id>
+<timestamp>2023-11-20T10:00:00Z</timestamp>
+<text xml:space="preserve">{{Other Languages
+|ja = 璃月
+|ja_rm = Riiyue
+}}
+[[Category:Regions]]</text>
+</revision>
+</page>
+<page>
+<title>User:Foo</title>
+<ns>2</ns>
+<id>101</id>
+<revision>
+<id>6</id>
+<timestamp>2023-11-21T10:00:00Z</timestamp>
+<text xml:space="preserve">[[Category:Users]]</text>
+</revision>
+</page>
+</mediawiki>"#;
+
+    let filter = PageFilter {
+        namespaces: vec![0, 2],
+        ..Default::default()
+    };
+    let pages = parse_database_dump(
+        input.as_bytes(),
+        filter,
+        WikiProfile::builtin("genshin").unwrap(),
+    )
+    .collect::<anyhow::Result<Vec<_>>>()
+    .unwrap();
+
+    assert_eq!(pages.len(), 2);
+    assert_eq!(pages[0].title, "Liyue");
+    assert_eq!(pages[0].namespace, 0);
+    assert_eq!(pages[0].id, 100);
+    assert_eq!(pages[0].revision.timestamp, "2023-11-20T10:00:00Z");
+    assert_eq!(pages[0].revision.text.categories, vec!["Regions"]);
+    assert_eq!(pages[0].revision.text.other_languages.len(), 1);
+    assert_eq!(pages[1].title, "User:Foo");
+    assert_eq!(pages[1].namespace, 2);
+    assert_eq!(pages[1].id, 101);
+    assert_eq!(pages[1].revision.text.categories, vec!["Users"]);
+}
+
+#[test]
+fn test_parse_database_dump_filter() {
+    let input = r#"<mediawiki>
+<page><title>Liyue</title><ns>0</ns><id>1</id><revision><text>Liyue</text></revision></page>
+<page><title>Template:Foo</title><ns>10</ns><id>2</id><revision><text>Foo</text></revision></page>
+<page><title>Li Yue</title><ns>0</ns><id>3</id><redirect title="Liyue" /><revision><text>#REDIRECT [[Liyue]]</text></revision></page>
+<page><title>LiYue</title><ns>0</ns><id>4</id><revision><text>#redirect [[Liyue]]</text></revision></page>
+</mediawiki>"#;
+
+    let titles = |filter| {
+        parse_database_dump(
+            input.as_bytes(),
+            filter,
+            WikiProfile::builtin("genshin").unwrap(),
+        )
+        .map(|page| page.unwrap().title)
+        .collect::<Vec<_>>()
+    };
+
+    assert_eq!(titles(PageFilter::default()), vec!["Liyue"]);
+    assert_eq!(
+        titles(PageFilter {
+            namespaces: vec![0, 10],
+            include_redirects: true,
+        }),
+        vec!["Liyue", "Template:Foo", "Li Yue", "LiYue"]
+    );
+}
+
+#[test]
+fn test_to_ime_dictionary_entry() {
+    let input = r#"<mediawiki>
+<page>
+<title>Inazuma City</title>
+<ns>0</ns>
+<id>1</id>
+<revision>
+<text xml:space="preserve">{{Other Languages
+|ja = {{Rubi|稲妻城|いなずまじょう}}
+|ja_rm = Inazuma-jou
+}}</text>
+</revision>
+</page>
+<page>
+<title>Venti</title>
+<ns>0</ns>
+<id>2</id>
+<revision>
+<text xml:space="preserve">{{Other Languages
+|ja = ウェンティ
+|ja_rm = Wenti
+}}</text>
+</revision>
+</page>
+<page>
+<title>Liyue</title>
+<ns>0</ns>
+<id>3</id>
+<revision>
+<text xml:space="preserve">{{Other Languages
+|ja = 璃月
+|ja_rm = Riiyue
+|ko = 리월
+|ko_rm = Riwol
+}}</text>
+</revision>
+</page>
+</mediawiki>"#;
+
+    let entries = |language| {
+        parse_database_dump(
+            input.as_bytes(),
+            PageFilter::default(),
+            WikiProfile::builtin("genshin").unwrap(),
+        )
+        .flat_map(|page| {
+            to_ime_dictionary_entry(&page.unwrap(), language, false, RomajiMode::default())
+                .collect::<Vec<_>>()
+        })
+        .collect::<Vec<_>>()
+    };
+
+    assert_eq!(
+        entries(Language::Japanese)
+            .into_iter()
+            .map(|entry| (entry.word, entry.yomi))
+            .collect::<Vec<_>>(),
+        vec![
+            ("稲妻城".to_string(), "いなずまじょう".to_string()),
+            ("ウェンティ".to_string(), "うぇんてぃ".to_string()),
+            ("璃月".to_string(), "りいゆえ".to_string()),
+        ]
+    );
+
+    let korean = entries(Language::Korean);
+    assert_eq!(korean.len(), 1);
+    assert_eq!(korean[0].word, "리월");
+    assert_eq!(korean[0].yomi, "ㄹㅇ");
+    assert_eq!(korean[0].romanization.as_deref(), Some("Riwol"));
+}
+
+#[test]
+fn test_to_ime_dictionary_entry_category() {
+    let input = r#"<mediawiki>
+<page>
+<title>Raiden Shogun</title>
+<ns>0</ns>
+<id>1</id>
+<revision>
+<text xml:space="preserve">{{Character Infobox
+|name = Raiden Shogun
+}}
+== Signature Weapon ==
+{{Weapon Infobox
+|name = Engulfing Lightning
+}}
+{{Other Languages
+|ja = 草薙の稲光
+|ja_rm = Kusanagi no Inabikari
+}}
+== Other Languages ==
+{{Other Languages
+|ja = 雷電将軍
+|ja_rm = Raiden Shougun
+}}</text>
+</revision>
+</page>
+</mediawiki>"#;
+
+    let entries = parse_database_dump(
+        input.as_bytes(),
+        PageFilter::default(),
+        WikiProfile::builtin("genshin").unwrap(),
+    )
+    .flat_map(|page| {
+        to_ime_dictionary_entry(
+            &page.unwrap(),
+            Language::Japanese,
+            false,
+            RomajiMode::default(),
+        )
+        .collect::<Vec<_>>()
+    })
+    .map(|entry| (entry.word, entry.category))
+    .collect::<Vec<_>>();
+
+    assert_eq!(
+        entries,
+        vec![
+            ("草薙の稲光".to_string(), WordCategory::Noun),
+            ("雷電将軍".to_string(), WordCategory::Person),
+        ]
+    );
+}
+
+#[test]
+fn test_apply_overrides() {
+    let overrides: Overrides = toml::from_str(
+        r#"
+[extra_yomi]
+"璃月" = ["りげつ"]
+"#,
+    )
+    .unwrap();
+    let mut dictionary = test_dictionary(vec![
+        test_entry("璃月", "りーゆえ", "Liyue"),
+        test_entry("稲妻", "いなずま", "Inazuma"),
+        test_entry("稲妻", "いなづま", "Inazuma City"),
+        test_entry("璃月", "りーゆえ", "Liyue Harbor"),
+    ]);
+
+    let (unused, collisions) = apply_overrides(&mut dictionary, &[overrides]);
+    assert_eq!(unused, vec![Vec::<String>::new()]);
+    // 追加した読みは衝突として扱わない
+    assert_eq!(
+        collisions,
+        vec![ReadingCollision {
+            word: "稲妻".to_string(),
+            yomis: vec!["いなずま".to_string(), "いなづま".to_string()],
+        }]
+    );
+    assert_eq!(
+        dictionary
+            .entries
+            .iter()
+            .map(|entry| (entry.word.as_str(), entry.yomi.as_str()))
+            .collect::<Vec<_>>(),
+        vec![
+            ("璃月", "りーゆえ"),
+            ("稲妻", "いなずま"),
+            ("稲妻", "いなづま"),
+            ("璃月", "りげつ"),
+        ]
+    );
+}
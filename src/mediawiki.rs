@@ -20,6 +20,44 @@ pub fn parse_mediawiki(input: &str) -> Vec<Node<'_>> {
     parse(tokenize(input))
 }
 
+/// `[[Category:...]]`形式のリンクからカテゴリ名を取り出す。ソートキー (`|...`) は取り除く。
+pub fn parse_categories(input: &str) -> impl Iterator<Item = &str> + '_ {
+    input.split("[[").skip(1).filter_map(|link| {
+        let link = &link[..link.find("]]")?];
+        let (namespace, name) = link.split_once(':')?;
+        if !namespace.trim().eq_ignore_ascii_case("category") {
+            return None;
+        }
+        let name = name.split('|').next()?.trim();
+        (!name.is_empty()).then_some(name)
+    })
+}
+
+/// 見出し (`== ... ==`の行) で本文を節に分ける。見出しの行は次の節の先頭に含まれる。
+pub fn split_sections(input: &str) -> impl Iterator<Item = &str> + '_ {
+    let mut rest = input;
+    std::iter::from_fn(move || {
+        if rest.is_empty() {
+            return None;
+        }
+        let mut offset = 0;
+        for line in rest.split_inclusive('\n') {
+            let trimmed = line.trim_end();
+            if offset > 0
+                && trimmed.len() >= 2
+                && trimmed.starts_with('=')
+                && trimmed.ends_with('=')
+            {
+                break;
+            }
+            offset += line.len();
+        }
+        let (section, next) = rest.split_at(offset);
+        rest = next;
+        Some(section)
+    })
+}
+
 #[derive(Debug, PartialEq)]
 enum Token<'a> {
     Text(&'a str),
@@ -274,3 +312,26 @@ fn test_parse() {
         ]
     );
 }
+
+#[test]
+fn test_parse_categories() {
+    let input = "aaa [[Category:Liyue]] [[Link|text]] [[category: Regions |sort]] [[Category:]]";
+    let categories = parse_categories(input).collect::<Vec<_>>();
+
+    assert_eq!(categories, vec!["Liyue", "Regions"]);
+}
+
+#[test]
+fn test_split_sections() {
+    let input =
+        "{{Character Infobox}}\nLead\n== Weapon ==\n{{Weapon Infobox}}\n=== Notes ===\nNote";
+    assert_eq!(
+        split_sections(input).collect::<Vec<_>>(),
+        vec![
+            "{{Character Infobox}}\nLead\n",
+            "== Weapon ==\n{{Weapon Infobox}}\n",
+            "=== Notes ===\nNote"
+        ]
+    );
+    assert_eq!(split_sections("").count(), 0);
+}
@@ -0,0 +1,221 @@
+use std::{collections::BTreeMap, path::Path};
+
+use serde::Deserialize;
+
+use crate::{classify::WordCategory, load_toml, Dictionary, IMEDictionaryEntry};
+
+/// 日本語の辞書に常に適用する修正
+const JAPANESE_OVERRIDES: &str = include_str!("../overrides/ja.toml");
+
+/// 生成した辞書に対する手動の修正 (TOML形式)
+///
+/// ```toml
+/// drop = ["旅人"]
+///
+/// [replace_yomi]
+/// "りいうぇ" = "りーゆえ"
+///
+/// [yomi]
+/// "璃月" = "りーゆえ"
+///
+/// [extra_yomi]
+/// "璃月" = ["りげつ"]
+///
+/// [[entries]]
+/// word = "テイワット"
+/// yomi = "ていわっと"
+/// category = "place"
+/// ```
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Overrides {
+    /// 削除する単語
+    pub drop: Vec<String>,
+    /// 読みに含まれる文字列を置き換える
+    pub replace_yomi: BTreeMap<String, String>,
+    /// 単語の読みを置き換える
+    pub yomi: BTreeMap<String, String>,
+    /// 単語に別の読みを追加する
+    pub extra_yomi: BTreeMap<String, Vec<String>>,
+    /// 追加する単語
+    pub entries: Vec<ExtraEntry>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ExtraEntry {
+    pub word: String,
+    pub yomi: String,
+    #[serde(default)]
+    pub category: Option<WordCategory>,
+}
+
+impl Overrides {
+    pub fn load(path: &Path) -> anyhow::Result<Overrides> {
+        load_toml(path)
+    }
+
+    /// 日本語の辞書に常に適用する修正 (`overrides/ja.toml`)
+    pub fn japanese() -> Overrides {
+        toml::from_str(JAPANESE_OVERRIDES).expect("overrides/ja.toml should be valid")
+    }
+
+    /// 辞書の単語を削除したり読みを置き換えたりする修正 (`drop`、`replace_yomi`、`yomi`) を適用する。
+    /// 返り値は辞書のどの単語にも当てはまらなかった修正の一覧。
+    pub fn apply_replacements(&self, dictionary: &mut Dictionary) -> Vec<String> {
+        let mut unused = Vec::new();
+        let contains = |entries: &[IMEDictionaryEntry], word: &str| {
+            entries.iter().any(|entry| entry.word == word)
+        };
+
+        for word in &self.drop {
+            if !contains(&dictionary.entries, word) {
+                unused.push(format!("drop: {}", word));
+            }
+        }
+        dictionary
+            .entries
+            .retain(|entry| !self.drop.contains(&entry.word));
+
+        for (from, to) in &self.replace_yomi {
+            let mut used = false;
+            for entry in &mut dictionary.entries {
+                if entry.yomi.contains(from.as_str()) {
+                    entry.yomi = entry.yomi.replace(from.as_str(), to);
+                    used = true;
+                }
+            }
+            if !used {
+                unused.push(format!("replace_yomi: {}", from));
+            }
+        }
+
+        for (word, yomi) in &self.yomi {
+            if !contains(&dictionary.entries, word) {
+                unused.push(format!("yomi: {}", word));
+            }
+            for entry in &mut dictionary.entries {
+                if &entry.word == word {
+                    entry.yomi = yomi.clone();
+                }
+            }
+        }
+
+        unused
+    }
+
+    /// 辞書に読みや単語を追加する修正 (`extra_yomi`、`entries`) を適用する。
+    /// 返り値は辞書のどの単語にも当てはまらなかった修正の一覧。
+    pub fn apply_additions(&self, dictionary: &mut Dictionary) -> Vec<String> {
+        let mut unused = Vec::new();
+
+        for (word, yomis) in &self.extra_yomi {
+            let Some(entry) = dictionary.entries.iter().find(|entry| &entry.word == word) else {
+                unused.push(format!("extra_yomi: {}", word));
+                continue;
+            };
+            let extra = yomis
+                .iter()
+                .map(|yomi| IMEDictionaryEntry {
+                    word: entry.word.clone(),
+                    yomi: yomi.clone(),
+                    page_titles: entry.page_titles.clone(),
+                    category: entry.category,
+                    romanization: entry.romanization.clone(),
+                })
+                .collect::<Vec<_>>();
+            dictionary.entries.extend(extra);
+        }
+
+        for extra in &self.entries {
+            if dictionary
+                .entries
+                .iter()
+                .any(|entry| entry.word == extra.word && entry.yomi == extra.yomi)
+            {
+                unused.push(format!("entries: {} ({})", extra.word, extra.yomi));
+                continue;
+            }
+            dictionary.entries.push(IMEDictionaryEntry {
+                word: extra.word.clone(),
+                yomi: extra.yomi.clone(),
+                page_titles: Vec::new(),
+                category: extra.category.unwrap_or(WordCategory::ProperNoun),
+                romanization: None,
+            });
+        }
+
+        unused
+    }
+}
+
+#[test]
+fn test_apply() {
+    let overrides: Overrides = toml::from_str(
+        r#"
+drop = ["旅人", "パイモン"]
+
+[replace_yomi]
+"うぇ" = "ゆえ"
+"あ" = "い"
+
+[yomi]
+"璃月" = "りーゆえ"
+
+[extra_yomi]
+"璃月" = ["りげつ"]
+"稲妻" = ["いなずま"]
+
+[[entries]]
+word = "テイワット"
+yomi = "ていわっと"
+category = "place"
+
+[[entries]]
+word = "モンド"
+yomi = "もんど"
+"#,
+    )
+    .unwrap();
+
+    use crate::{test_dictionary, test_entry};
+
+    let mut dictionary = test_dictionary(vec![
+        test_entry("璃月", "りいうぇ", "Page"),
+        test_entry("旅人", "たびびと", "Page"),
+        test_entry("モンド", "もんど", "Page"),
+    ]);
+
+    let mut unused = overrides.apply_replacements(&mut dictionary);
+    unused.extend(overrides.apply_additions(&mut dictionary));
+    assert_eq!(
+        unused,
+        vec![
+            "drop: パイモン",
+            "replace_yomi: あ",
+            "extra_yomi: 稲妻",
+            "entries: モンド (もんど)"
+        ]
+    );
+
+    let entries = dictionary
+        .entries
+        .iter()
+        .map(|entry| (entry.word.as_str(), entry.yomi.as_str(), entry.category))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        entries,
+        vec![
+            ("璃月", "りーゆえ", WordCategory::ProperNoun),
+            ("モンド", "もんど", WordCategory::ProperNoun),
+            ("璃月", "りげつ", WordCategory::ProperNoun),
+            ("テイワット", "ていわっと", WordCategory::Place),
+        ]
+    );
+
+    let mut dictionary = test_dictionary(vec![test_entry("璃月港", "りいうぇこう", "Page")]);
+    assert!(Overrides::japanese()
+        .apply_replacements(&mut dictionary)
+        .is_empty());
+    assert_eq!(dictionary.entries[0].yomi, "りーゆえこう");
+}
@@ -0,0 +1,104 @@
+/// 声調記号なしの拼音の音節。`ü`は`v`で表す。
+const SYLLABLES: &[&str] = &[
+    "a", "ai", "an", "ang", "ao", "ba", "bai", "ban", "bang", "bao", "bei", "ben", "beng", "bi",
+    "bian", "biao", "bie", "bin", "bing", "bo", "bu", "ca", "cai", "can", "cang", "cao", "ce",
+    "cen", "ceng", "cha", "chai", "chan", "chang", "chao", "che", "chen", "cheng", "chi", "chong",
+    "chou", "chu", "chua", "chuai", "chuan", "chuang", "chui", "chun", "chuo", "ci", "cong", "cou",
+    "cu", "cuan", "cui", "cun", "cuo", "da", "dai", "dan", "dang", "dao", "de", "dei", "den",
+    "deng", "di", "dia", "dian", "diao", "die", "ding", "diu", "dong", "dou", "du", "duan", "dui",
+    "dun", "duo", "e", "ei", "en", "eng", "er", "fa", "fan", "fang", "fei", "fen", "feng", "fo",
+    "fou", "fu", "ga", "gai", "gan", "gang", "gao", "ge", "gei", "gen", "geng", "gong", "gou",
+    "gu", "gua", "guai", "guan", "guang", "gui", "gun", "guo", "ha", "hai", "han", "hang", "hao",
+    "he", "hei", "hen", "heng", "hong", "hou", "hu", "hua", "huai", "huan", "huang", "hui", "hun",
+    "huo", "ji", "jia", "jian", "jiang", "jiao", "jie", "jin", "jing", "jiong", "jiu", "ju",
+    "juan", "jue", "jun", "ka", "kai", "kan", "kang", "kao", "ke", "kei", "ken", "keng", "kong",
+    "kou", "ku", "kua", "kuai", "kuan", "kuang", "kui", "kun", "kuo", "la", "lai", "lan", "lang",
+    "lao", "le", "lei", "leng", "li", "lia", "lian", "liang", "liao", "lie", "lin", "ling", "liu",
+    "lo", "long", "lou", "lu", "luan", "lun", "luo", "lv", "lve", "ma", "mai", "man", "mang",
+    "mao", "me", "mei", "men", "meng", "mi", "mian", "miao", "mie", "min", "ming", "miu", "mo",
+    "mou", "mu", "na", "nai", "nan", "nang", "nao", "ne", "nei", "nen", "neng", "ni", "nian",
+    "niang", "niao", "nie", "nin", "ning", "niu", "nong", "nou", "nu", "nuan", "nun", "nuo", "nv",
+    "nve", "o", "ou", "pa", "pai", "pan", "pang", "pao", "pei", "pen", "peng", "pi", "pian",
+    "piao", "pie", "pin", "ping", "po", "pou", "pu", "qi", "qia", "qian", "qiang", "qiao", "qie",
+    "qin", "qing", "qiong", "qiu", "qu", "quan", "que", "qun", "ran", "rang", "rao", "re", "ren",
+    "reng", "ri", "rong", "rou", "ru", "rua", "ruan", "rui", "run", "ruo", "sa", "sai", "san",
+    "sang", "sao", "se", "sen", "seng", "sha", "shai", "shan", "shang", "shao", "she", "shei",
+    "shen", "sheng", "shi", "shou", "shu", "shua", "shuai", "shuan", "shuang", "shui", "shun",
+    "shuo", "si", "song", "sou", "su", "suan", "sui", "sun", "suo", "ta", "tai", "tan", "tang",
+    "tao", "te", "tei", "teng", "ti", "tian", "tiao", "tie", "ting", "tong", "tou", "tu", "tuan",
+    "tui", "tun", "tuo", "wa", "wai", "wan", "wang", "wei", "wen", "weng", "wo", "wu", "xi", "xia",
+    "xian", "xiang", "xiao", "xie", "xin", "xing", "xiong", "xiu", "xu", "xuan", "xue", "xun",
+    "ya", "yan", "yang", "yao", "ye", "yi", "yin", "ying", "yo", "yong", "you", "yu", "yuan",
+    "yue", "yun", "za", "zai", "zan", "zang", "zao", "ze", "zei", "zen", "zeng", "zha", "zhai",
+    "zhan", "zhang", "zhao", "zhe", "zhei", "zhen", "zheng", "zhi", "zhong", "zhou", "zhu", "zhua",
+    "zhuai", "zhuan", "zhuang", "zhui", "zhun", "zhuo", "zi", "zong", "zou", "zu", "zuan", "zui",
+    "zun", "zuo",
+];
+
+/// 声調記号付きの拼音を音節に分割する。
+/// 音節は声調記号を取り除いた小文字で、`ü`は`v`に置き換えられる。
+/// 拼音として解釈できない場合は`None`を返す。
+pub fn pinyin_to_syllables(pinyin: &str) -> Option<Vec<String>> {
+    let mut normalized = String::new();
+    for c in pinyin.chars() {
+        normalized.push(match c {
+            'ā' | 'á' | 'ǎ' | 'à' | 'Ā' | 'Á' | 'Ǎ' | 'À' => 'a',
+            'ē' | 'é' | 'ě' | 'è' | 'Ē' | 'É' | 'Ě' | 'È' => 'e',
+            'ī' | 'í' | 'ǐ' | 'ì' | 'Ī' | 'Í' | 'Ǐ' | 'Ì' => 'i',
+            'ō' | 'ó' | 'ǒ' | 'ò' | 'Ō' | 'Ó' | 'Ǒ' | 'Ò' => 'o',
+            'ū' | 'ú' | 'ǔ' | 'ù' | 'Ū' | 'Ú' | 'Ǔ' | 'Ù' => 'u',
+            'ǖ' | 'ǘ' | 'ǚ' | 'ǜ' | 'ü' | 'Ǖ' | 'Ǘ' | 'Ǚ' | 'Ǜ' | 'Ü' => 'v',
+            c if c.is_ascii_alphabetic() => c.to_ascii_lowercase(),
+            c if c.is_whitespace() || matches!(c, '\'' | '’' | '-' | '·' | '・' | '.') => ' ',
+            _ => return None,
+        });
+    }
+
+    let mut syllables = Vec::new();
+    for word in normalized.split_whitespace() {
+        split_syllables(word, &mut syllables)?;
+    }
+
+    if syllables.is_empty() {
+        None
+    } else {
+        Some(syllables)
+    }
+}
+
+/// 区切りのない拼音を最長一致で音節に分割する。分割できない場合は短い音節で再試行する。
+fn split_syllables(word: &str, syllables: &mut Vec<String>) -> Option<()> {
+    if word.is_empty() {
+        return Some(());
+    }
+
+    for len in (1..=word.len().min(6)).rev() {
+        let syllable = &word[..len];
+        if SYLLABLES.contains(&syllable) {
+            syllables.push(syllable.to_string());
+            if split_syllables(&word[len..], syllables).is_some() {
+                return Some(());
+            }
+            syllables.pop();
+        }
+    }
+
+    None
+}
+
+#[test]
+fn test_pinyin_to_syllables() {
+    assert_eq!(
+        pinyin_to_syllables("Líyuè"),
+        Some(vec!["li".to_string(), "yue".to_string()])
+    );
+    assert_eq!(
+        pinyin_to_syllables("Xiāngling"),
+        Some(vec!["xiang".to_string(), "ling".to_string()])
+    );
+    assert_eq!(
+        pinyin_to_syllables("Xī'ān Nǚ"),
+        Some(vec!["xi".to_string(), "an".to_string(), "nv".to_string()])
+    );
+    assert_eq!(pinyin_to_syllables("Dainsleif"), None);
+}
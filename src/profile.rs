@@ -0,0 +1,152 @@
+use std::path::Path;
+
+use serde::Deserialize;
+
+use crate::load_toml;
+
+/// 辞書の元にするWikiの設定
+#[derive(Debug, Clone, Deserialize)]
+pub struct WikiProfile {
+    /// データベースダンプ (7zアーカイブ) のURL
+    pub dump_url: String,
+    /// アーカイブに含まれるXMLファイルの名前
+    pub archive_member: String,
+    /// 他言語での名称が書かれたテンプレートの名前
+    #[serde(default = "default_template_name")]
+    pub template_name: String,
+    #[serde(default)]
+    pub arguments: TemplateArguments,
+}
+
+/// 他言語での名称が書かれたテンプレートの引数名
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct TemplateArguments {
+    pub ja: String,
+    pub ja_rm: String,
+    pub zhs: String,
+    pub zhs_rm: String,
+    pub zht: String,
+    pub zht_rm: String,
+    pub ko: String,
+    pub ko_rm: String,
+}
+
+impl Default for TemplateArguments {
+    fn default() -> Self {
+        TemplateArguments {
+            ja: "ja".to_string(),
+            ja_rm: "ja_rm".to_string(),
+            zhs: "zhs".to_string(),
+            zhs_rm: "zhs_rm".to_string(),
+            zht: "zht".to_string(),
+            zht_rm: "zht_rm".to_string(),
+            ko: "ko".to_string(),
+            ko_rm: "ko_rm".to_string(),
+        }
+    }
+}
+
+fn default_template_name() -> String {
+    "Other Languages".to_string()
+}
+
+/// 組み込みのWikiの名前
+pub const BUILTIN_PROFILES: &[&str] = &["genshin", "hsr", "zzz"];
+
+impl WikiProfile {
+    /// 組み込みのWikiの設定を返す。
+    pub fn builtin(name: &str) -> anyhow::Result<WikiProfile> {
+        let fandom = |database: &str| WikiProfile {
+            dump_url: format!(
+                "https://s3.amazonaws.com/wikia_xml_dumps/{}/{}/{}_pages_current.xml.7z",
+                &database[..1],
+                &database[..2],
+                database
+            ),
+            archive_member: format!("{}_pages_current.xml", database),
+            template_name: default_template_name(),
+            arguments: TemplateArguments::default(),
+        };
+
+        match name {
+            // Genshin Impact Wiki
+            "genshin" => Ok(fandom("gensinimpact")),
+            // Honkai: Star Rail Wiki
+            "hsr" => Ok(fandom("honkaistarrail")),
+            // Zenless Zone Zero Wiki
+            "zzz" => Ok(fandom("zenlesszonezero")),
+            _ => anyhow::bail!(
+                "Unknown wiki: {} (available: {})",
+                name,
+                BUILTIN_PROFILES.join(", ")
+            ),
+        }
+    }
+
+    /// TOML形式の設定ファイルからWikiの設定を読み込む。
+    pub fn load(path: &Path) -> anyhow::Result<WikiProfile> {
+        load_toml(path)
+    }
+
+    /// ダウンロードしたアーカイブを保存するときのファイル名
+    pub fn archive_filename(&self) -> &str {
+        self.dump_url
+            .rsplit('/')
+            .next()
+            .filter(|name| !name.is_empty())
+            .unwrap_or("pages_current.xml.7z")
+    }
+}
+
+#[test]
+fn test_builtin() {
+    let profile = WikiProfile::builtin("genshin").unwrap();
+    assert_eq!(
+        profile.dump_url,
+        "https://s3.amazonaws.com/wikia_xml_dumps/g/ge/gensinimpact_pages_current.xml.7z"
+    );
+    assert_eq!(profile.archive_member, "gensinimpact_pages_current.xml");
+    assert_eq!(
+        profile.archive_filename(),
+        "gensinimpact_pages_current.xml.7z"
+    );
+
+    let profile = WikiProfile::builtin("hsr").unwrap();
+    assert_eq!(
+        profile.dump_url,
+        "https://s3.amazonaws.com/wikia_xml_dumps/h/ho/honkaistarrail_pages_current.xml.7z"
+    );
+    assert_eq!(profile.archive_member, "honkaistarrail_pages_current.xml");
+
+    let profile = WikiProfile::builtin("zzz").unwrap();
+    assert_eq!(
+        profile.dump_url,
+        "https://s3.amazonaws.com/wikia_xml_dumps/z/ze/zenlesszonezero_pages_current.xml.7z"
+    );
+    assert_eq!(profile.archive_member, "zenlesszonezero_pages_current.xml");
+
+    assert!(WikiProfile::builtin("unknown").is_err());
+}
+
+#[test]
+fn test_load() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    let path = temp_dir.path().join("wiki.toml");
+    std::fs::write(
+        &path,
+        r#"
+dump_url = "https://example.com/dumps/example_pages_current.xml.7z"
+archive_member = "example_pages_current.xml"
+
+[arguments]
+ja = "jp"
+"#,
+    )
+    .unwrap();
+
+    let profile = WikiProfile::load(&path).unwrap();
+    assert_eq!(profile.template_name, "Other Languages");
+    assert_eq!(profile.arguments.ja, "jp");
+    assert_eq!(profile.arguments.ja_rm, "ja_rm");
+}
@@ -1,970 +1,535 @@
-pub fn romaji_to_hiragana(romaji: &str, useー: bool) -> String {
+use std::{fmt, ops::Range};
+
+use crate::kana::long_o_spellings;
+
+/// ローマ字とかなの対応表。ヘボン式、日本式、訓令式、ワープロ式のつづりを含む。
+///
+/// 変換は最長一致で行うため、同じ文字で始まるつづりの順序は問わない。
+/// 促音 (`kk`など) と撥音 (`n`、`m`) の一部は表ではなく[`romaji_to_hiragana`]で扱う。
+const TABLE: &[(&str, &str)] = &[
+    ("a", "あ"),
+    ("i", "い"),
+    ("u", "う"),
+    ("e", "え"),
+    ("o", "お"),
+    // か行
+    ("ka", "か"),
+    ("ki", "き"),
+    ("ku", "く"),
+    ("ke", "け"),
+    ("ko", "こ"),
+    ("kya", "きゃ"),
+    ("kyu", "きゅ"),
+    ("kyo", "きょ"),
+    ("ga", "が"),
+    ("gi", "ぎ"),
+    ("gu", "ぐ"),
+    ("ge", "げ"),
+    ("go", "ご"),
+    ("gya", "ぎゃ"),
+    ("gyu", "ぎゅ"),
+    ("gyo", "ぎょ"),
+    // さ行
+    ("sa", "さ"),
+    ("shi", "し"),
+    ("si", "し"),
+    ("su", "す"),
+    ("se", "せ"),
+    ("so", "そ"),
+    ("sha", "しゃ"),
+    ("shu", "しゅ"),
+    ("sho", "しょ"),
+    ("she", "しぇ"),
+    ("sya", "しゃ"),
+    ("syu", "しゅ"),
+    ("syo", "しょ"),
+    ("za", "ざ"),
+    ("ji", "じ"),
+    ("zi", "じ"),
+    ("zu", "ず"),
+    ("ze", "ぜ"),
+    ("zo", "ぞ"),
+    ("ja", "じゃ"),
+    ("ju", "じゅ"),
+    ("jo", "じょ"),
+    ("je", "じぇ"),
+    ("zya", "じゃ"),
+    ("zyu", "じゅ"),
+    ("zyo", "じょ"),
+    ("jya", "じゃ"),
+    ("jyu", "じゅ"),
+    ("jyo", "じょ"),
+    // た行
+    ("ta", "た"),
+    ("chi", "ち"),
+    ("ti", "ち"),
+    ("tsu", "つ"),
+    ("tu", "つ"),
+    ("te", "て"),
+    ("to", "と"),
+    ("cha", "ちゃ"),
+    ("chu", "ちゅ"),
+    ("cho", "ちょ"),
+    ("che", "ちぇ"),
+    ("tya", "ちゃ"),
+    ("tyu", "ちゅ"),
+    ("tyo", "ちょ"),
+    ("cya", "ちゃ"),
+    ("cyu", "ちゅ"),
+    ("cyo", "ちょ"),
+    ("tsa", "つぁ"),
+    ("tsi", "つぃ"),
+    ("tse", "つぇ"),
+    ("tso", "つぉ"),
+    ("da", "だ"),
+    ("di", "ぢ"),
+    ("du", "づ"),
+    ("dzu", "づ"),
+    ("de", "で"),
+    ("do", "ど"),
+    ("dya", "ぢゃ"),
+    ("dyu", "ぢゅ"),
+    ("dyo", "ぢょ"),
+    // な行
+    ("na", "な"),
+    ("ni", "に"),
+    ("nu", "ぬ"),
+    ("ne", "ね"),
+    ("no", "の"),
+    ("nya", "にゃ"),
+    ("nyu", "にゅ"),
+    ("nyo", "にょ"),
+    ("n", "ん"),
+    // は行
+    ("ha", "は"),
+    ("hi", "ひ"),
+    ("fu", "ふ"),
+    ("hu", "ふ"),
+    ("he", "へ"),
+    ("ho", "ほ"),
+    ("hya", "ひゃ"),
+    ("hyu", "ひゅ"),
+    ("hyo", "ひょ"),
+    ("fa", "ふぁ"),
+    ("fi", "ふぃ"),
+    ("fe", "ふぇ"),
+    ("fo", "ふぉ"),
+    ("ba", "ば"),
+    ("bi", "び"),
+    ("bu", "ぶ"),
+    ("be", "べ"),
+    ("bo", "ぼ"),
+    ("bya", "びゃ"),
+    ("byu", "びゅ"),
+    ("byo", "びょ"),
+    ("pa", "ぱ"),
+    ("pi", "ぴ"),
+    ("pu", "ぷ"),
+    ("pe", "ぺ"),
+    ("po", "ぽ"),
+    ("pya", "ぴゃ"),
+    ("pyu", "ぴゅ"),
+    ("pyo", "ぴょ"),
+    ("va", "ゔぁ"),
+    ("vi", "ゔぃ"),
+    ("vu", "ゔ"),
+    ("ve", "ゔぇ"),
+    ("vo", "ゔぉ"),
+    // ま行
+    ("ma", "ま"),
+    ("mi", "み"),
+    ("mu", "む"),
+    ("me", "め"),
+    ("mo", "も"),
+    ("mya", "みゃ"),
+    ("myu", "みゅ"),
+    ("myo", "みょ"),
+    // や行
+    ("ya", "や"),
+    ("yu", "ゆ"),
+    ("yo", "よ"),
+    // ら行
+    ("ra", "ら"),
+    ("ri", "り"),
+    ("ru", "る"),
+    ("re", "れ"),
+    ("ro", "ろ"),
+    ("rya", "りゃ"),
+    ("ryu", "りゅ"),
+    ("ryo", "りょ"),
+    // わ行
+    ("wa", "わ"),
+    ("wi", "うぃ"),
+    ("we", "うぇ"),
+    ("wo", "を"),
+    // 外来語の音
+    ("kwa", "くぁ"),
+    ("kwi", "くぃ"),
+    ("kwe", "くぇ"),
+    ("kwo", "くぉ"),
+    ("gwa", "ぐぁ"),
+    ("gwi", "ぐぃ"),
+    ("gwe", "ぐぇ"),
+    ("gwo", "ぐぉ"),
+    ("ye", "いぇ"),
+    ("who", "うぉ"),
+    ("thi", "てぃ"),
+    ("dhi", "でぃ"),
+    ("thu", "てゅ"),
+    ("dhu", "でゅ"),
+    ("twu", "とぅ"),
+    ("dwu", "どぅ"),
+    ("fyu", "ふゅ"),
+    ("vyu", "ゔゅ"),
+    // 小書きのかな (ワープロ式)
+    ("xa", "ぁ"),
+    ("xi", "ぃ"),
+    ("xu", "ぅ"),
+    ("xe", "ぇ"),
+    ("xo", "ぉ"),
+    ("xya", "ゃ"),
+    ("xyu", "ゅ"),
+    ("xyo", "ょ"),
+    ("xwa", "ゎ"),
+    ("xtu", "っ"),
+    ("xtsu", "っ"),
+    ("la", "ぁ"),
+    ("li", "ぃ"),
+    ("lu", "ぅ"),
+    ("le", "ぇ"),
+    ("lo", "ぉ"),
+    ("lya", "ゃ"),
+    ("lyu", "ゅ"),
+    ("lyo", "ょ"),
+    ("lwa", "ゎ"),
+    ("ltu", "っ"),
+    ("ltsu", "っ"),
+];
+
+/// [`RomajiMode::Loanword`]のときに[`TABLE`]の代わりに使う、外来語の音としての読み
+const LOANWORD_TABLE: &[(&str, &str)] = &[
+    ("ti", "てぃ"),
+    ("tu", "とぅ"),
+    ("di", "でぃ"),
+    ("du", "どぅ"),
+];
+
+/// 外来語の音と訓令式・日本式で読み方が異なるつづり (`ti`、`tu`など) の扱い
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, clap::ValueEnum)]
+pub enum RomajiMode {
+    /// 訓令式・日本式のつづりとして読む (`ti`は`ち`)
+    #[default]
+    Kunrei,
+    /// ヘボン式で外来語の音を表すつづりとして読む (`ti`は`てぃ`)
+    Loanword,
+}
+
+/// 表のつづりの最大の長さ
+const MAX_LEN: usize = 4;
+
+/// 表から`romaji`の先頭に最長一致するつづりを探し、つづりの長さとかなを返す。
+fn lookup(romaji: &str, mode: RomajiMode) -> Option<(usize, &'static str)> {
+    (1..=MAX_LEN.min(romaji.len())).rev().find_map(|len| {
+        let spelling = romaji.get(..len)?;
+        let find = |table: &[(&str, &'static str)]| {
+            table
+                .iter()
+                .find(|(key, _)| *key == spelling)
+                .map(|&(_, kana)| kana)
+        };
+        match mode {
+            RomajiMode::Loanword => find(LOANWORD_TABLE).or_else(|| find(TABLE)),
+            RomajiMode::Kunrei => find(TABLE),
+        }
+        .map(|kana| (len, kana))
+    })
+}
+
+fn is_vowel(c: char) -> bool {
+    matches!(c, 'a' | 'i' | 'u' | 'e' | 'o')
+}
+
+/// ローマ字として変換できなかった部分
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RomajiError {
+    /// 変換できなかった部分の、元のローマ字でのバイト位置
+    pub span: Range<usize>,
+    /// 変換できなかった部分の最初の文字
+    pub character: char,
+}
+
+impl fmt::Display for RomajiError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "unconvertible character {:?} at {}..{}",
+            self.character, self.span.start, self.span.end
+        )
+    }
+}
+
+impl std::error::Error for RomajiError {}
+
+/// 長音符号 (マクロン、サーカムフレックス) 付きの母音を、小文字の母音と`ー`に分ける。
+/// 結合文字の長音符号も`ー`にする。
+/// 2つ目の返り値は、変換後の各バイトに対応する元の文字のバイト位置 (末尾に元の長さを含む)。
+fn normalize_long_vowels(romaji: &str) -> (String, Vec<usize>) {
+    let mut normalized = String::new();
+    let mut origins = Vec::new();
+    for (i, c) in romaji.char_indices() {
+        match c {
+            'ā' | 'â' | 'Ā' | 'Â' => normalized.push_str("aー"),
+            'ī' | 'î' | 'Ī' | 'Î' => normalized.push_str("iー"),
+            'ū' | 'û' | 'Ū' | 'Û' => normalized.push_str("uー"),
+            'ē' | 'ê' | 'Ē' | 'Ê' => normalized.push_str("eー"),
+            'ō' | 'ô' | 'Ō' | 'Ô' => normalized.push_str("oー"),
+            '\u{0304}' | '\u{0302}' => normalized.push('ー'),
+            c => normalized.push(c.to_ascii_lowercase()),
+        }
+        origins.resize(normalized.len(), i);
+    }
+    origins.push(romaji.len());
+    (normalized, origins)
+}
+
+/// 単語の区切りとして読み飛ばす文字。
+/// `n'`や`n-`のように`ん`と次の母音や`y`を分けるのにも使われる。
+fn is_separator(c: char) -> bool {
+    c.is_whitespace() || matches!(c, '-' | '\'' | '’' | '‘' | 'ʼ' | '.' | '·' | '・')
+}
+
+/// ローマ字をひらがなに変換する。
+/// `useー`が`true`の場合、同じ母音が続くところと長音符号 (`ō`、`ô`など) を`ー`にする。
+/// `useー`が`false`の場合、長音符号は母音のかなにする。
+/// `ō`は`word`のかなの部分の書き方 (`トオル`なら`おお`) に合わせ、決められない場合は`おう`にする。
+/// `ti`、`tu`などのつづりは`mode`に従って変換する。
+/// 変換できない文字は区切り (空白や`-`など) を除いてそのまま残す。
+pub fn romaji_to_hiragana(romaji: &str, word: &str, useー: bool, mode: RomajiMode) -> String {
+    convert(romaji, word, useー, mode).0
+}
+
+/// [`romaji_to_hiragana`]と同じように変換するが、
+/// 変換できない文字 (対応するかながない文字、母音の続かない子音、
+/// 単語の先頭の小書きのかなを表す`l`や`x`など) があればエラーを返す。
+pub fn romaji_to_hiragana_strict(
+    romaji: &str,
+    word: &str,
+    useー: bool,
+    mode: RomajiMode,
+) -> Result<String, RomajiError> {
+    let (hiragana, errors) = convert(romaji, word, useー, mode);
+    match errors.into_iter().next() {
+        Some(error) => Err(error),
+        None => Ok(hiragana),
+    }
+}
+
+/// ローマ字をひらがなに変換し、変換できなかった部分の一覧とともに返す。
+fn convert(romaji: &str, word: &str, useー: bool, mode: RomajiMode) -> (String, Vec<RomajiError>) {
+    let original = romaji;
+    let (romaji, origins) = normalize_long_vowels(romaji);
     let mut hiragana = String::new();
+    let mut errors = Vec::<RomajiError>::new();
 
-    let mut chars = romaji.char_indices().peekable();
+    // 単語のかなの部分と`ō`の数が一致する場合だけ、その書き方を順に使う
+    let mut long_o = long_o_spellings(word).into_iter();
+    if long_o.len() != romaji.matches("oー").count() {
+        long_o = Vec::new().into_iter();
+    }
 
-    while let Some((i, next)) = chars.next() {
-        match next.to_ascii_lowercase() {
-            'a' => {
-                if useー && i > 0 && &romaji[i - 1..=i - 1] == "a" {
-                    hiragana.push('ー');
-                } else {
-                    hiragana.push('あ');
-                }
-            }
-            'i' => {
-                if useー && i > 0 && &romaji[i - 1..=i - 1] == "i" {
-                    hiragana.push('ー');
-                } else {
-                    hiragana.push('い');
-                }
-            }
-            'u' => {
-                if useー && i > 0 && &romaji[i - 1..=i - 1] == "u" {
-                    hiragana.push('ー');
-                } else {
-                    hiragana.push('う');
-                }
-            }
-            'e' => {
-                if useー && i > 0 && &romaji[i - 1..=i - 1] == "e" {
-                    hiragana.push('ー');
-                } else {
-                    hiragana.push('え');
-                }
-            }
-            'o' => {
-                if useー && i > 0 && &romaji[i - 1..=i - 1] == "o" {
-                    hiragana.push('ー');
-                } else {
-                    hiragana.push('お');
-                }
-            }
-            'k' => match if let Some((_, peek)) = chars.peek() {
-                peek
-            } else {
-                break;
-            }
-            .to_ascii_lowercase()
-            {
-                'a' => {
-                    chars.next();
-                    hiragana.push('か');
-                }
-                'i' => {
-                    chars.next();
-                    hiragana.push('き');
-                }
-                'u' => {
-                    chars.next();
-                    hiragana.push('く');
-                }
-                'e' => {
-                    chars.next();
-                    hiragana.push('け');
-                }
-                'o' => {
-                    chars.next();
-                    hiragana.push('こ');
-                }
-                'k' => {
-                    hiragana.push('っ');
-                }
-                'y' => {
-                    chars.next();
-                    match if let Some((_, next)) = chars.next() {
-                        next
-                    } else {
-                        break;
-                    }
-                    .to_ascii_lowercase()
-                    {
-                        'a' => {
-                            hiragana.push('き');
-                            hiragana.push('ゃ');
-                        }
-                        'u' => {
-                            hiragana.push('き');
-                            hiragana.push('ゅ');
-                        }
-                        'o' => {
-                            hiragana.push('き');
-                            hiragana.push('ょ');
-                        }
-                        _ => {}
-                    }
-                }
-                _ => {}
-            },
-            's' => match if let Some((_, peek)) = chars.peek() {
-                peek
-            } else {
-                break;
-            }
-            .to_ascii_lowercase()
-            {
-                'a' => {
-                    chars.next();
-                    hiragana.push('さ');
-                }
-                'i' => {
-                    chars.next();
-                    hiragana.push('し');
-                }
-                'u' => {
-                    chars.next();
-                    hiragana.push('す');
-                }
-                'e' => {
-                    chars.next();
-                    hiragana.push('せ');
-                }
-                'o' => {
-                    chars.next();
-                    hiragana.push('そ');
-                }
-                's' => {
-                    hiragana.push('っ');
-                }
-                'h' => {
-                    chars.next();
-                    match if let Some((_, next)) = chars.next() {
-                        next
-                    } else {
-                        break;
-                    }
-                    .to_ascii_lowercase()
-                    {
-                        'a' => {
-                            hiragana.push('し');
-                            hiragana.push('ゃ');
-                        }
-                        'i' => {
-                            hiragana.push('し');
-                        }
-                        'u' => {
-                            hiragana.push('し');
-                            hiragana.push('ゅ');
-                        }
-                        'e' => {
-                            hiragana.push('し');
-                            hiragana.push('ぇ');
-                        }
-                        'o' => {
-                            hiragana.push('し');
-                            hiragana.push('ょ');
-                        }
-                        _ => {}
-                    }
-                }
-                'y' => {
-                    chars.next();
-                    match if let Some((_, next)) = chars.next() {
-                        next
-                    } else {
-                        break;
-                    }
-                    .to_ascii_lowercase()
-                    {
-                        'a' => {
-                            hiragana.push('し');
-                            hiragana.push('ゃ');
-                        }
-                        'u' => {
-                            hiragana.push('し');
-                            hiragana.push('ゅ');
-                        }
-                        'o' => {
-                            hiragana.push('し');
-                            hiragana.push('ょ');
-                        }
-                        _ => {}
-                    }
-                }
-                _ => {}
-            },
-            't' => match if let Some((_, peek)) = chars.peek() {
-                peek
-            } else {
-                break;
-            }
-            .to_ascii_lowercase()
-            {
-                'a' => {
-                    chars.next();
-                    hiragana.push('た');
-                }
-                'i' => {
-                    chars.next();
-                    hiragana.push('て');
-                    hiragana.push('ぃ');
-                }
-                'u' => {
-                    chars.next();
-                    hiragana.push('と');
-                    hiragana.push('ぅ');
-                }
-                'e' => {
-                    chars.next();
-                    hiragana.push('て');
-                }
-                'o' => {
-                    chars.next();
-                    hiragana.push('と');
-                }
-                't' => {
-                    hiragana.push('っ');
-                }
-                's' => {
-                    chars.next();
-                    match if let Some((_, next)) = chars.next() {
-                        next
-                    } else {
-                        break;
-                    }
-                    .to_ascii_lowercase()
-                    {
-                        'a' => {
-                            hiragana.push('つ');
-                            hiragana.push('ぁ');
-                        }
-                        'i' => {
-                            hiragana.push('つ');
-                            hiragana.push('ぃ');
-                        }
-                        'u' => hiragana.push('つ'),
-                        'e' => {
-                            hiragana.push('つ');
-                            hiragana.push('ぇ');
-                        }
-                        'o' => {
-                            hiragana.push('つ');
-                            hiragana.push('ぉ');
-                        }
-                        _ => {}
-                    }
-                }
-                'y' => {
-                    chars.next();
-                    match if let Some((_, next)) = chars.next() {
-                        next
-                    } else {
-                        break;
-                    }
-                    .to_ascii_lowercase()
-                    {
-                        'a' => {
-                            hiragana.push('ち');
-                            hiragana.push('ゃ');
-                        }
-                        'u' => {
-                            hiragana.push('ち');
-                            hiragana.push('ゅ');
-                        }
-                        'o' => {
-                            hiragana.push('ち');
-                            hiragana.push('ょ');
-                        }
-                        _ => {}
-                    }
-                }
-                _ => {}
-            },
-            'c' => match if let Some((_, peek)) = chars.peek() {
-                peek
-            } else {
-                break;
-            }
-            .to_ascii_lowercase()
-            {
-                'h' => {
-                    chars.next();
-                    match if let Some((_, next)) = chars.next() {
-                        next
-                    } else {
-                        break;
-                    }
-                    .to_ascii_lowercase()
-                    {
-                        'a' => {
-                            hiragana.push('ち');
-                            hiragana.push('ゃ');
-                        }
-                        'i' => {
-                            hiragana.push('ち');
-                        }
-                        'u' => {
-                            hiragana.push('ち');
-                            hiragana.push('ゅ');
-                        }
-                        'e' => {
-                            hiragana.push('ち');
-                            hiragana.push('ぇ');
-                        }
-                        'o' => {
-                            hiragana.push('ち');
-                            hiragana.push('ょ');
-                        }
-                        _ => {}
-                    }
-                }
-                'c' => {
-                    hiragana.push('っ');
-                }
-                _ => {}
-            },
-            'n' => match if let Some((_, peek)) = chars.peek() {
-                peek
-            } else {
-                hiragana.push('ん');
-                break;
-            }
-            .to_ascii_lowercase()
-            {
-                'a' => {
-                    chars.next();
-                    hiragana.push('な');
-                }
-                'i' => {
-                    chars.next();
-                    hiragana.push('に');
-                }
-                'u' => {
-                    chars.next();
-                    hiragana.push('ぬ');
-                }
-                'e' => {
-                    chars.next();
-                    hiragana.push('ね');
-                }
-                'o' => {
-                    chars.next();
-                    hiragana.push('の');
-                }
-                'n' => {
-                    hiragana.push('ん');
-                }
-                'y' => {
-                    chars.next();
-                    match if let Some((_, next)) = chars.next() {
-                        next
-                    } else {
-                        break;
-                    }
-                    .to_ascii_lowercase()
-                    {
-                        'a' => {
-                            hiragana.push('に');
-                            hiragana.push('ゃ');
-                        }
-                        'u' => {
-                            hiragana.push('に');
-                            hiragana.push('ゅ');
-                        }
-                        'o' => {
-                            hiragana.push('に');
-                            hiragana.push('ょ');
-                        }
-                        _ => {}
-                    }
-                }
-                _ => {
-                    hiragana.push('ん');
-                }
-            },
-            'h' => match if let Some((_, peek)) = chars.peek() {
-                peek
-            } else {
-                break;
-            }
-            .to_ascii_lowercase()
-            {
-                'a' => {
-                    chars.next();
-                    hiragana.push('は');
-                }
-                'i' => {
-                    chars.next();
-                    hiragana.push('ひ');
-                }
-                'u' => {
-                    chars.next();
-                    hiragana.push('ふ');
-                }
-                'e' => {
-                    chars.next();
-                    hiragana.push('へ');
-                }
-                'o' => {
-                    chars.next();
-                    hiragana.push('ほ');
-                }
-                'h' => {
-                    hiragana.push('っ');
-                }
-                'y' => {
-                    chars.next();
-                    match if let Some((_, next)) = chars.next() {
-                        next
-                    } else {
-                        break;
-                    }
-                    .to_ascii_lowercase()
-                    {
-                        'a' => {
-                            hiragana.push('ひ');
-                            hiragana.push('ゃ');
-                        }
-                        'u' => {
-                            hiragana.push('ひ');
-                            hiragana.push('ゅ');
-                        }
-                        'o' => {
-                            hiragana.push('ひ');
-                            hiragana.push('ょ');
-                        }
-                        _ => {}
-                    }
-                }
-                _ => {}
-            },
-            'f' => match if let Some((_, peek)) = chars.peek() {
-                peek
-            } else {
-                break;
-            }
-            .to_ascii_lowercase()
-            {
-                'a' => {
-                    chars.next();
-                    hiragana.push('ふ');
-                    hiragana.push('ぁ');
-                }
-                'i' => {
-                    chars.next();
-                    hiragana.push('ふ');
-                    hiragana.push('ぃ');
-                }
-                'u' => {
-                    chars.next();
-                    hiragana.push('ふ');
-                }
-                'e' => {
-                    chars.next();
-                    hiragana.push('ふ');
-                    hiragana.push('ぇ');
-                }
-                'o' => {
-                    chars.next();
-                    hiragana.push('ふ');
-                    hiragana.push('ぉ');
-                }
-                'f' => {
-                    hiragana.push('っ');
-                }
-                _ => {}
-            },
-            'm' => match if let Some((_, peek)) = chars.peek() {
-                peek
-            } else {
-                break;
-            }
-            .to_ascii_lowercase()
-            {
-                'a' => {
-                    chars.next();
-                    hiragana.push('ま');
-                }
-                'i' => {
-                    chars.next();
-                    hiragana.push('み');
-                }
-                'u' => {
-                    chars.next();
-                    hiragana.push('む');
-                }
-                'e' => {
-                    chars.next();
-                    hiragana.push('め');
-                }
-                'o' => {
-                    chars.next();
-                    hiragana.push('も');
-                }
-                'm' => {
-                    hiragana.push('っ');
-                }
-                'y' => {
-                    chars.next();
-                    match if let Some((_, next)) = chars.next() {
-                        next
-                    } else {
-                        break;
-                    }
-                    .to_ascii_lowercase()
-                    {
-                        'a' => {
-                            hiragana.push('み');
-                            hiragana.push('ゃ');
-                        }
-                        'u' => {
-                            hiragana.push('み');
-                            hiragana.push('ゅ');
-                        }
-                        'o' => {
-                            hiragana.push('み');
-                            hiragana.push('ょ');
-                        }
-                        _ => {}
-                    }
-                }
-                _ => {}
-            },
-            'y' => match if let Some((_, peek)) = chars.peek() {
-                peek
-            } else {
-                break;
-            }
-            .to_ascii_lowercase()
-            {
-                'a' => {
-                    chars.next();
-                    hiragana.push('や');
-                }
-                'u' => {
-                    chars.next();
-                    hiragana.push('ゆ');
-                }
-                'o' => {
-                    chars.next();
-                    hiragana.push('よ');
-                }
-                'y' => {
-                    hiragana.push('っ');
-                }
-                _ => {}
-            },
-            'r' => match if let Some((_, peek)) = chars.peek() {
-                peek
-            } else {
-                break;
-            }
-            .to_ascii_lowercase()
-            {
-                'a' => {
-                    chars.next();
-                    hiragana.push('ら');
-                }
-                'i' => {
-                    chars.next();
-                    hiragana.push('り');
-                }
-                'u' => {
-                    chars.next();
-                    hiragana.push('る');
-                }
-                'e' => {
-                    chars.next();
-                    hiragana.push('れ');
-                }
-                'o' => {
-                    chars.next();
-                    hiragana.push('ろ');
-                }
-                'r' => {
-                    hiragana.push('っ');
-                }
-                'y' => {
-                    chars.next();
-                    match if let Some((_, next)) = chars.next() {
-                        next
-                    } else {
-                        break;
-                    }
-                    .to_ascii_lowercase()
-                    {
-                        'a' => {
-                            hiragana.push('り');
-                            hiragana.push('ゃ');
-                        }
-                        'u' => {
-                            hiragana.push('り');
-                            hiragana.push('ゅ');
-                        }
-                        'o' => {
-                            hiragana.push('り');
-                            hiragana.push('ょ');
-                        }
-                        _ => {}
-                    }
-                }
-                _ => {}
-            },
-            'w' => match if let Some((_, peek)) = chars.peek() {
-                peek
-            } else {
-                break;
-            }
-            .to_ascii_lowercase()
-            {
-                'a' => {
-                    chars.next();
-                    hiragana.push('わ');
-                }
-                'i' => {
-                    chars.next();
-                    hiragana.push('う');
-                    hiragana.push('ぃ');
-                }
-                'e' => {
-                    chars.next();
-                    hiragana.push('う');
-                    hiragana.push('ぇ');
-                }
-                'o' => {
-                    chars.next();
-                    hiragana.push('を');
-                }
-                'w' => {
-                    hiragana.push('っ');
-                }
-                _ => {}
-            },
-            'g' => match if let Some((_, peek)) = chars.peek() {
-                peek
-            } else {
-                break;
-            }
-            .to_ascii_lowercase()
-            {
-                'a' => {
-                    chars.next();
-                    hiragana.push('が');
-                }
-                'i' => {
-                    chars.next();
-                    hiragana.push('ぎ');
-                }
-                'u' => {
-                    chars.next();
-                    hiragana.push('ぐ');
-                }
-                'e' => {
-                    chars.next();
-                    hiragana.push('げ');
-                }
-                'o' => {
-                    chars.next();
-                    hiragana.push('ご');
-                }
-                'g' => {
-                    hiragana.push('っ');
-                }
-                'y' => {
-                    chars.next();
-                    match if let Some((_, next)) = chars.next() {
-                        next
-                    } else {
-                        break;
-                    }
-                    .to_ascii_lowercase()
-                    {
-                        'a' => {
-                            hiragana.push('ぎ');
-                            hiragana.push('ゃ');
-                        }
-                        'u' => {
-                            hiragana.push('ぎ');
-                            hiragana.push('ゅ');
-                        }
-                        'o' => {
-                            hiragana.push('ぎ');
-                            hiragana.push('ょ');
-                        }
-                        _ => {}
-                    }
-                }
-                _ => {}
-            },
-            'z' => match if let Some((_, peek)) = chars.peek() {
-                peek
-            } else {
-                break;
-            }
-            .to_ascii_lowercase()
-            {
-                'a' => {
-                    chars.next();
-                    hiragana.push('ざ');
-                }
-                'i' => {
-                    chars.next();
-                    hiragana.push('じ');
-                }
-                'u' => {
-                    chars.next();
-                    hiragana.push('ず');
-                }
-                'e' => {
-                    chars.next();
-                    hiragana.push('ぜ');
-                }
-                'o' => {
-                    chars.next();
-                    hiragana.push('ぞ');
-                }
-                'z' => {
-                    hiragana.push('っ');
-                }
-                'y' => {
-                    chars.next();
-                    match if let Some((_, next)) = chars.next() {
-                        next
-                    } else {
-                        break;
-                    }
-                    .to_ascii_lowercase()
-                    {
-                        'a' => {
-                            hiragana.push('じ');
-                            hiragana.push('ゃ');
-                        }
-                        'u' => {
-                            hiragana.push('じ');
-                            hiragana.push('ゅ');
-                        }
-                        'o' => {
-                            hiragana.push('じ');
-                            hiragana.push('ょ');
-                        }
-                        _ => {}
-                    }
-                }
-                _ => {}
-            },
-            'j' => match if let Some((_, peek)) = chars.peek() {
-                peek
-            } else {
-                break;
-            }
-            .to_ascii_lowercase()
-            {
-                'a' => {
-                    chars.next();
-                    hiragana.push('じ');
-                    hiragana.push('ゃ');
-                }
-                'i' => {
-                    chars.next();
-                    hiragana.push('じ');
-                }
-                'u' => {
-                    chars.next();
-                    hiragana.push('じ');
-                    hiragana.push('ゅ');
-                }
-                'e' => {
-                    chars.next();
-                    hiragana.push('じ');
-                    hiragana.push('ぇ');
-                }
-                'o' => {
-                    chars.next();
-                    hiragana.push('じ');
-                    hiragana.push('ょ');
-                }
-                _ => {}
-            },
-            'd' => match if let Some((_, peek)) = chars.peek() {
-                peek
-            } else {
-                break;
-            }
-            .to_ascii_lowercase()
-            {
-                'a' => {
-                    chars.next();
-                    hiragana.push('だ');
-                }
-                'i' => {
-                    chars.next();
-                    hiragana.push('で');
-                    hiragana.push('ぃ');
-                }
-                'u' => {
-                    chars.next();
-                    hiragana.push('ど');
-                    hiragana.push('ぅ');
-                }
-                'e' => {
-                    chars.next();
-                    hiragana.push('で');
-                }
-                'o' => {
-                    chars.next();
-                    hiragana.push('ど');
-                }
-                'd' => {
-                    hiragana.push('っ');
-                }
-                _ => {}
-            },
-            'b' => match if let Some((_, peek)) = chars.peek() {
-                peek
-            } else {
-                break;
-            }
-            .to_ascii_lowercase()
-            {
-                'a' => {
-                    chars.next();
-                    hiragana.push('ば');
-                }
-                'i' => {
-                    chars.next();
-                    hiragana.push('び');
-                }
-                'u' => {
-                    chars.next();
-                    hiragana.push('ぶ');
-                }
-                'e' => {
-                    chars.next();
-                    hiragana.push('べ');
-                }
-                'o' => {
-                    chars.next();
-                    hiragana.push('ぼ');
-                }
-                'b' => {
-                    hiragana.push('っ');
-                }
-                'y' => {
-                    chars.next();
-                    match if let Some((_, next)) = chars.next() {
-                        next
-                    } else {
-                        break;
-                    }
-                    .to_ascii_lowercase()
-                    {
-                        'a' => {
-                            hiragana.push('び');
-                            hiragana.push('ゃ');
-                        }
-                        'u' => {
-                            hiragana.push('び');
-                            hiragana.push('ゅ');
-                        }
-                        'o' => {
-                            hiragana.push('び');
-                            hiragana.push('ょ');
-                        }
-                        _ => {}
-                    }
-                }
-                _ => {}
-            },
-            'v' => match if let Some((_, peek)) = chars.peek() {
-                peek
+    let mut position = 0;
+    let mut previous = None;
+
+    while let Some(c) = romaji[position..].chars().next() {
+        let rest = &romaji[position..];
+        let next = rest[c.len_utf8()..].chars().next();
+
+        let (len, kana) = if c == 'ー' && (useー || previous.is_some_and(is_vowel)) {
+            // 長音符号
+            let kana = match previous {
+                _ if useー => "ー",
+                Some('a') => "あ",
+                Some('i') => "い",
+                Some('u') => "う",
+                Some('e') => "え",
+                _ => match long_o.next() {
+                    Some('お') => "お",
+                    _ => "う",
+                },
+            };
+            (c.len_utf8(), kana)
+        } else if useー && is_vowel(c) && previous == Some(c) {
+            (1, "ー")
+        } else if c.is_ascii_alphabetic()
+            && !is_vowel(c)
+            && !matches!(c, 'n' | 'm')
+            && next == Some(c)
+        {
+            // 促音 (`nn`と`mm`は撥音)
+            (1, "っ")
+        } else if c == 'n' && next == Some('n') {
+            // 母音か`y`の前の`nn`はヘボン式の`ん`+な行 (`konnichiwa`)、それ以外はワープロ式の`ん`
+            let after = rest[2..].chars().next();
+            if after.is_some_and(|after| is_vowel(after) || after == 'y') {
+                (1, "ん")
             } else {
-                break;
+                (2, "ん")
             }
-            .to_ascii_lowercase()
-            {
-                'a' => {
-                    chars.next();
-                    hiragana.push('ゔ');
-                    hiragana.push('ぁ');
-                }
-                'i' => {
-                    chars.next();
-                    hiragana.push('ゔ');
-                    hiragana.push('ぃ');
-                }
-                'u' => {
-                    chars.next();
-                    hiragana.push('ゔ');
-                }
-                'e' => {
-                    chars.next();
-                    hiragana.push('ゔ');
-                    hiragana.push('ぇ');
-                }
-                'o' => {
-                    chars.next();
-                    hiragana.push('ゔ');
-                    hiragana.push('ぉ');
-                }
-                'v' => {
-                    hiragana.push('っ');
-                }
-                _ => {}
-            },
-            'p' => match if let Some((_, peek)) = chars.peek() {
-                peek
+        } else if c == 't' && rest.starts_with("tch") {
+            // ヘボン式の`tch` (っち)
+            (1, "っ")
+        } else if c == 'm' && matches!(next, Some('b' | 'm' | 'p')) {
+            // ヘボン式の`b`、`m`、`p`の前の`m` (ん)
+            (1, "ん")
+        } else if let Some(found) = lookup(rest, mode)
+            // 小書きのかな (`la`、`xi`など) は前のかなに付けるもので、単語の先頭には置けない
+            .filter(|_| !(matches!(c, 'l' | 'x') && previous.is_none_or(is_separator)))
+        {
+            found
+        } else {
+            if is_separator(c) {
+                (c.len_utf8(), "")
             } else {
-                break;
+                let span = origins[position]..origins[position + c.len_utf8()];
+                // 長音符号や結合文字は元の文字の一部なので、元の文字を報告する
+                let character = original[span.start..].chars().next().unwrap_or(c);
+                // 同じ文字から続けて変換できなかった場合は1つにまとめる
+                match errors.last_mut() {
+                    Some(error) if error.span.end == span.start => error.span.end = span.end,
+                    _ => errors.push(RomajiError { span, character }),
+                }
+                (c.len_utf8(), &rest[..c.len_utf8()])
             }
-            .to_ascii_lowercase()
-            {
-                'a' => {
-                    chars.next();
-                    hiragana.push('ぱ');
-                }
-                'i' => {
-                    chars.next();
-                    hiragana.push('ぴ');
-                }
-                'u' => {
-                    chars.next();
-                    hiragana.push('ぷ');
-                }
-                'e' => {
-                    chars.next();
-                    hiragana.push('ぺ');
-                }
-                'o' => {
-                    chars.next();
-                    hiragana.push('ぽ');
-                }
-                'p' => {
-                    hiragana.push('っ');
-                }
-                'y' => {
-                    chars.next();
-                    match if let Some((_, next)) = chars.next() {
-                        next
-                    } else {
-                        break;
-                    }
-                    .to_ascii_lowercase()
-                    {
-                        'a' => {
-                            hiragana.push('ぴ');
-                            hiragana.push('ゃ');
-                        }
-                        'u' => {
-                            hiragana.push('ぴ');
-                            hiragana.push('ゅ');
-                        }
-                        'o' => {
-                            hiragana.push('ぴ');
-                            hiragana.push('ょ');
-                        }
-                        _ => {}
-                    }
-                }
-                _ => {}
-            },
-            _ => {}
-        }
+        };
+
+        hiragana.push_str(kana);
+        previous = rest[..len].chars().last();
+        position += len;
     }
 
-    hiragana
+    (hiragana, errors)
 }
 
 #[test]
 fn test() {
-    assert_eq!(romaji_to_hiragana("kaa", true), "かー");
+    let hiragana_ー = |romaji| romaji_to_hiragana(romaji, "", true, RomajiMode::Kunrei);
+
+    assert_eq!(hiragana_ー("kaa"), "かー");
+}
+
+#[test]
+fn test_syllabic_n() {
+    let hiragana = |romaji| romaji_to_hiragana(romaji, "", false, RomajiMode::Kunrei);
+    let hiragana_ー = |romaji| romaji_to_hiragana(romaji, "", true, RomajiMode::Kunrei);
+
+    // `n'`
+    assert_eq!(hiragana("Shin'ichi"), "しんいち");
+    assert_eq!(hiragana("Shin’ichi"), "しんいち");
+    assert_eq!(hiragana("Ten'yō"), "てんよう");
+    assert_eq!(hiragana("Hon'ya"), "ほんや");
+    assert_eq!(hiragana("Honya"), "ほにゃ");
+    // `n-`
+    assert_eq!(hiragana("Kan-en"), "かんえん");
+    assert_eq!(hiragana("Shin-yo"), "しんよ");
+    // `nn`
+    assert_eq!(hiragana("Konnichiwa"), "こんにちわ");
+    assert_eq!(hiragana("Anna"), "あんな");
+    assert_eq!(hiragana("Jinnai"), "じんない");
+    assert_eq!(hiragana("Shinnyo"), "しんにょ");
+    assert_eq!(hiragana("Kenn"), "けん");
+    assert_eq!(hiragana("Sennsei"), "せんせい");
+    assert_eq!(hiragana("Kann'i"), "かんい");
+    // 単語の途中の区切り
+    assert_eq!(hiragana("Inazuma Jou"), "いなずまじょう");
+    assert_eq!(hiragana_ー("Ai-iro"), "あいいろ");
+    assert_eq!(hiragana_ー("Ka an"), "かあん");
+    assert_eq!(
+        romaji_to_hiragana_strict("Shin'ichi Kan-en", "", false, RomajiMode::Kunrei),
+        Ok("しんいちかんえん".to_string())
+    );
+}
+
+#[test]
+fn test_spellings() {
+    let hiragana = |romaji| romaji_to_hiragana(romaji, "", false, RomajiMode::Kunrei);
+
+    assert_eq!(hiragana("Shinobu"), "しのぶ");
+    assert_eq!(hiragana("Sinobu"), "しのぶ");
+    assert_eq!(hiragana("Tsurumi"), "つるみ");
+    assert_eq!(hiragana("Fujin"), "ふじん");
+    assert_eq!(hiragana("Huzin"), "ふじん");
+    assert_eq!(hiragana("Tyouji"), "ちょうじ");
+    assert_eq!(hiragana("Kodzue"), "こづえ");
+    assert_eq!(hiragana("Hakkei"), "はっけい");
+    assert_eq!(hiragana("Matcha"), "まっちゃ");
+    assert_eq!(hiragana("Namba"), "なんば");
+    assert_eq!(hiragana("Hommachi"), "ほんまち");
+    assert_eq!(hiragana("Tukuyomi"), "つくよみ");
+    assert_eq!(hiragana("Kami wo Tsugu"), "かみをつぐ");
+}
+
+#[test]
+fn test_long_vowels() {
+    let hiragana = |romaji, word| romaji_to_hiragana(romaji, word, false, RomajiMode::Kunrei);
+    let hiragana_ー = |romaji| romaji_to_hiragana(romaji, "", true, RomajiMode::Kunrei);
+
+    assert_eq!(hiragana("Ōkami", ""), "おうかみ");
+    assert_eq!(hiragana("Yūki", ""), "ゆうき");
+    assert_eq!(hiragana("Tôkyô", "東京"), "とうきょう");
+    assert_eq!(hiragana("Kujō Sara", "九条裟羅"), "くじょうさら");
+    assert_eq!(hiragana("Tōru", "トオル"), "とおる");
+    assert_eq!(hiragana("Kōhei", "コウヘイ"), "こうへい");
+    // 漢字の部分の`ō`の書き方は分からないので、数が合わない場合はすべて`おう`にする
+    assert_eq!(hiragana("Ōno Tōru", "大野トオル"), "おうのとうる");
+    assert_eq!(hiragana("Onēsan", ""), "おねえさん");
+    assert_eq!(hiragana_ー("ŌKAMI"), "おーかみ");
+    assert_eq!(hiragana_ー("Yu\u{0304}ra"), "ゆーら");
+}
+
+#[test]
+fn test_strict() {
+    let strict = |romaji| romaji_to_hiragana_strict(romaji, "", false, RomajiMode::Kunrei);
+    let lenient = |romaji| romaji_to_hiragana(romaji, "", false, RomajiMode::Kunrei);
+    let error = |span, character| Err(RomajiError { span, character });
+
+    assert_eq!(strict("Shin-Ōkami"), Ok("しんおうかみ".to_string()));
+    assert_eq!(strict("Qiqi"), error(0..1, 'Q'));
+    assert_eq!(strict("Twinkle"), error(0..1, 'T'));
+    assert_eq!(strict("Kāk"), error(3..4, 'k'));
+    assert_eq!(strict("Lumine"), error(0..1, 'L'));
+    assert_eq!(strict("Xiao"), error(0..1, 'X'));
+    assert_eq!(strict("Shin Xiao"), error(5..6, 'X'));
+    assert_eq!(strict("Texi"), Ok("てぃ".to_string()));
+    assert_eq!(lenient("Qiqi"), "qいqい");
+    assert_eq!(lenient("Xiao"), "xいあお");
+}
+
+#[test]
+fn test_modes() {
+    let loanword = |romaji| romaji_to_hiragana(romaji, "", false, RomajiMode::Loanword);
+    let kunrei = |romaji| romaji_to_hiragana(romaji, "", false, RomajiMode::Kunrei);
+
+    assert_eq!(loanword("Tiko"), "てぃこ");
+    assert_eq!(kunrei("Tikara"), "ちから");
+    assert_eq!(loanword("Tuuru"), "とぅうる");
+    assert_eq!(kunrei("Tukuyomi"), "つくよみ");
+    assert_eq!(loanword("Dizuni"), "でぃずに");
+    assert_eq!(kunrei("Hanadi"), "はなぢ");
+    // `tyu`などと`wo`はどちらでも同じ
+    assert_eq!(loanword("Tyuuka"), "ちゅうか");
+    assert_eq!(kunrei("Tyuuka"), "ちゅうか");
+    assert_eq!(loanword("Kami wo Tsugu"), "かみをつぐ");
+    assert_eq!(kunrei("Wotome"), "をとめ");
+
+    // 外来語の音を表すつづり
+    assert_eq!(loanword("Fatui"), "ふぁとぅい");
+    assert_eq!(kunrei("Whotto"), "うぉっと");
+    assert_eq!(kunrei("Kwaiya"), "くぁいや");
+    assert_eq!(kunrei("Gwaiya"), "ぐぁいや");
+    assert_eq!(kunrei("Yeraru"), "いぇらる");
+    assert_eq!(kunrei("Thiruka"), "てぃるか");
+    assert_eq!(kunrei("Dhuku"), "でゅく");
+    assert_eq!(kunrei("Twurudo"), "とぅるど");
+    assert_eq!(kunrei("Fyuuri"), "ふゅうり");
+    assert_eq!(kunrei("Vyuu"), "ゔゅう");
 }
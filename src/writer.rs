@@ -0,0 +1,118 @@
+use std::{
+    io::{self, Write},
+    str::FromStr,
+};
+
+use crate::{Dictionary, Language};
+
+mod google;
+mod korean;
+mod msime;
+mod plist;
+mod rime;
+mod skk;
+mod sogou;
+
+pub use google::GoogleIMEWriter;
+pub use korean::{Fcitx5HangulWriter, KoreanWordListWriter};
+pub use msime::MicrosoftIMEWriter;
+pub use plist::PlistWriter;
+pub use rime::RimeDictWriter;
+pub use skk::SKKJisyoWriter;
+pub use sogou::SogouWriter;
+
+/// IME辞書を特定の形式で書き出す。
+pub trait DictionaryWriter {
+    fn write(&self, dictionary: &Dictionary, output: &mut dyn Write) -> io::Result<()>;
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum OutputFormat {
+    /// Google 日本語入力
+    #[default]
+    GoogleIME,
+    /// Microsoft IME
+    MicrosoftIME,
+    /// macOS / iOSの日本語入力
+    Plist,
+    /// SKK
+    SKKJisyo,
+    /// Rime (日本語)
+    RimeJapanese,
+    /// 搜狗拼音 (简体中文)
+    Sogou,
+    /// Rime (简体中文)
+    RimeChineseSimplified,
+    /// Rime (繁體中文)
+    RimeChineseTraditional,
+    /// 韓国語IME (単語と略語の一覧)
+    KoreanWordList,
+    /// fcitx5-hangul
+    Fcitx5Hangul,
+}
+
+impl OutputFormat {
+    pub fn writer(self) -> Box<dyn DictionaryWriter> {
+        match self {
+            OutputFormat::GoogleIME => Box::new(GoogleIMEWriter),
+            OutputFormat::MicrosoftIME => Box::new(MicrosoftIMEWriter),
+            OutputFormat::Plist => Box::new(PlistWriter),
+            OutputFormat::SKKJisyo => Box::new(SKKJisyoWriter),
+            OutputFormat::RimeJapanese => Box::new(RimeDictWriter { name: "genshin_ja" }),
+            OutputFormat::Sogou => Box::new(SogouWriter),
+            OutputFormat::RimeChineseSimplified => Box::new(RimeDictWriter {
+                name: "genshin_zh_hans",
+            }),
+            OutputFormat::RimeChineseTraditional => Box::new(RimeDictWriter {
+                name: "genshin_zh_hant",
+            }),
+            OutputFormat::KoreanWordList => Box::new(KoreanWordListWriter),
+            OutputFormat::Fcitx5Hangul => Box::new(Fcitx5HangulWriter),
+        }
+    }
+
+    /// この形式で出力する単語の言語
+    pub fn language(self) -> Language {
+        match self {
+            OutputFormat::GoogleIME
+            | OutputFormat::MicrosoftIME
+            | OutputFormat::Plist
+            | OutputFormat::SKKJisyo
+            | OutputFormat::RimeJapanese => Language::Japanese,
+            OutputFormat::Sogou | OutputFormat::RimeChineseSimplified => {
+                Language::ChineseSimplified
+            }
+            OutputFormat::RimeChineseTraditional => Language::ChineseTraditional,
+            OutputFormat::KoreanWordList | OutputFormat::Fcitx5Hangul => Language::Korean,
+        }
+    }
+}
+
+impl FromStr for OutputFormat {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "google" => Ok(OutputFormat::GoogleIME),
+            "msime" => Ok(OutputFormat::MicrosoftIME),
+            "plist" => Ok(OutputFormat::Plist),
+            "skk" => Ok(OutputFormat::SKKJisyo),
+            "rime-ja" => Ok(OutputFormat::RimeJapanese),
+            "sogou" => Ok(OutputFormat::Sogou),
+            "rime-zh-hans" => Ok(OutputFormat::RimeChineseSimplified),
+            "rime-zh-hant" => Ok(OutputFormat::RimeChineseTraditional),
+            "korean" => Ok(OutputFormat::KoreanWordList),
+            "fcitx5-hangul" => Ok(OutputFormat::Fcitx5Hangul),
+            _ => anyhow::bail!("Unknown output format: {}", s),
+        }
+    }
+}
+
+/// BOM付きUTF-16LEで書き出す。
+fn write_utf16le(output: &mut dyn Write, text: &str) -> io::Result<()> {
+    output.write_all(&[0xFF, 0xFE])?;
+    for unit in text.encode_utf16() {
+        output.write_all(&unit.to_le_bytes())?;
+    }
+    Ok(())
+}
@@ -0,0 +1,47 @@
+use std::io::{self, Write};
+
+use crate::{Dictionary, WordCategory};
+
+use super::DictionaryWriter;
+
+/// Google 日本語入力のユーザー辞書形式 (UTF-8、タブ区切り) で書き出す。
+pub struct GoogleIMEWriter;
+
+impl DictionaryWriter for GoogleIMEWriter {
+    fn write(&self, dictionary: &Dictionary, output: &mut dyn Write) -> io::Result<()> {
+        for entry in &dictionary.entries {
+            writeln!(
+                output,
+                "{}\t{}\t{}",
+                entry.yomi,
+                entry.word,
+                part_of_speech(entry.category)
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// Google 日本語入力の品詞名
+fn part_of_speech(category: WordCategory) -> &'static str {
+    match category {
+        WordCategory::Person => "人名",
+        WordCategory::Place => "地名",
+        WordCategory::Organization => "組織",
+        WordCategory::Noun => "名詞",
+        WordCategory::ProperNoun => "固有名詞",
+    }
+}
+
+#[test]
+fn test_write() {
+    use crate::{test_dictionary, test_entry};
+
+    let dictionary = test_dictionary(vec![crate::IMEDictionaryEntry {
+        category: WordCategory::Place,
+        ..test_entry("璃月", "りーゆえ", "Liyue")
+    }]);
+    let mut output = Vec::new();
+    GoogleIMEWriter.write(&dictionary, &mut output).unwrap();
+    assert_eq!(String::from_utf8(output).unwrap(), "りーゆえ\t璃月\t地名\n");
+}
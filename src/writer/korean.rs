@@ -0,0 +1,76 @@
+use std::io::{self, Write};
+
+use crate::Dictionary;
+
+use super::DictionaryWriter;
+
+/// 韓国語IMEのユーザー辞書に取り込める`単語<TAB>略語`形式で書き出す。
+pub struct KoreanWordListWriter;
+
+impl DictionaryWriter for KoreanWordListWriter {
+    fn write(&self, dictionary: &Dictionary, output: &mut dyn Write) -> io::Result<()> {
+        for entry in &dictionary.entries {
+            writeln!(output, "{}\t{}", entry.word, entry.yomi)?;
+        }
+        Ok(())
+    }
+}
+
+/// fcitx5-hangul (libhangul) の`hanja.txt`と同じ`キー:値:注釈`形式で書き出す。
+///
+/// 注釈にはローマ字表記 (`ko_rm`) と出典のページを書く。
+/// この形式には`:`のエスケープがないため、`:`を含む単語は出力しない。
+pub struct Fcitx5HangulWriter;
+
+impl DictionaryWriter for Fcitx5HangulWriter {
+    fn write(&self, dictionary: &Dictionary, output: &mut dyn Write) -> io::Result<()> {
+        for entry in &dictionary.entries {
+            if entry.word.contains(':') {
+                continue;
+            }
+            let page_titles = entry.page_titles.join(", ");
+            let comment = match &entry.romanization {
+                Some(romanization) => format!("{} ({})", romanization, page_titles),
+                None => page_titles,
+            };
+            writeln!(
+                output,
+                "{}:{}:{}",
+                entry.yomi,
+                entry.word,
+                comment.replace(':', " ")
+            )?;
+        }
+        Ok(())
+    }
+}
+
+#[test]
+fn test_write() {
+    use crate::{test_dictionary, test_entry};
+
+    let dictionary = test_dictionary(vec![
+        crate::IMEDictionaryEntry {
+            romanization: Some("Riwol".to_string()),
+            ..test_entry("리월", "ㄹㅇ", "Liyue")
+        },
+        test_entry("몬드 성", "ㅁㄷㅅ", "Mondstadt: City"),
+        test_entry("라이덴: 쇼군", "ㄹㅇㄷㅅㄱ", "Raiden Shogun"),
+    ]);
+
+    let mut output = Vec::new();
+    KoreanWordListWriter
+        .write(&dictionary, &mut output)
+        .unwrap();
+    assert_eq!(
+        String::from_utf8(output).unwrap(),
+        "리월\tㄹㅇ\n몬드 성\tㅁㄷㅅ\n라이덴: 쇼군\tㄹㅇㄷㅅㄱ\n"
+    );
+
+    let mut output = Vec::new();
+    Fcitx5HangulWriter.write(&dictionary, &mut output).unwrap();
+    assert_eq!(
+        String::from_utf8(output).unwrap(),
+        "ㄹㅇ:리월:Riwol (Liyue)\nㅁㄷㅅ:몬드 성:Mondstadt  City\n"
+    );
+}
@@ -0,0 +1,56 @@
+use std::io::{self, Write};
+
+use crate::{Dictionary, WordCategory};
+
+use super::{write_utf16le, DictionaryWriter};
+
+/// Microsoft IMEのテキスト形式 (BOM付きUTF-16LE、CRLF改行) で書き出す。
+pub struct MicrosoftIMEWriter;
+
+impl DictionaryWriter for MicrosoftIMEWriter {
+    fn write(&self, dictionary: &Dictionary, output: &mut dyn Write) -> io::Result<()> {
+        let mut text = String::new();
+        text.push_str("!Microsoft IME Dictionary Tool\r\n");
+        text.push_str("!Format:WORDLIST\r\n");
+        text.push_str("\r\n");
+        for entry in &dictionary.entries {
+            text.push_str(&format!(
+                "{}\t{}\t{}\r\n",
+                entry.yomi,
+                entry.word,
+                part_of_speech(entry.category)
+            ));
+        }
+
+        write_utf16le(output, &text)
+    }
+}
+
+/// Microsoft IMEの品詞名。組織を表す品詞はないので固有名詞とする。
+fn part_of_speech(category: WordCategory) -> &'static str {
+    match category {
+        WordCategory::Person => "人名",
+        WordCategory::Place => "地名",
+        WordCategory::Organization | WordCategory::ProperNoun => "固有名詞",
+        WordCategory::Noun => "名詞",
+    }
+}
+
+#[test]
+fn test_write() {
+    use crate::{test_dictionary, test_entry};
+
+    let dictionary = test_dictionary(vec![test_entry("璃月", "りーゆえ", "Liyue")]);
+    let mut output = Vec::new();
+    MicrosoftIMEWriter.write(&dictionary, &mut output).unwrap();
+
+    assert_eq!(&output[..2], &[0xFF, 0xFE]);
+    let units = output[2..]
+        .chunks(2)
+        .map(|c| u16::from_le_bytes([c[0], c[1]]))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        String::from_utf16(&units).unwrap(),
+        "!Microsoft IME Dictionary Tool\r\n!Format:WORDLIST\r\n\r\nりーゆえ\t璃月\t固有名詞\r\n"
+    );
+}
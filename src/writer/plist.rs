@@ -0,0 +1,47 @@
+use std::io::{self, Write};
+
+use quick_xml::escape::escape;
+
+use crate::Dictionary;
+
+use super::DictionaryWriter;
+
+/// macOS / iOSのユーザー辞書 (XMLプロパティリスト) 形式で書き出す。
+pub struct PlistWriter;
+
+impl DictionaryWriter for PlistWriter {
+    fn write(&self, dictionary: &Dictionary, output: &mut dyn Write) -> io::Result<()> {
+        writeln!(output, r#"<?xml version="1.0" encoding="UTF-8"?>"#)?;
+        writeln!(
+            output,
+            r#"<!DOCTYPE plist PUBLIC "-//Apple//DTD PLIST 1.0//EN" "http://www.apple.com/DTDs/PropertyList-1.0.dtd">"#
+        )?;
+        writeln!(output, r#"<plist version="1.0">"#)?;
+        writeln!(output, "<array>")?;
+        for entry in &dictionary.entries {
+            writeln!(output, "\t<dict>")?;
+            writeln!(output, "\t\t<key>phrase</key>")?;
+            writeln!(output, "\t\t<string>{}</string>", escape(&entry.word))?;
+            writeln!(output, "\t\t<key>shortcut</key>")?;
+            writeln!(output, "\t\t<string>{}</string>", escape(&entry.yomi))?;
+            writeln!(output, "\t</dict>")?;
+        }
+        writeln!(output, "</array>")?;
+        writeln!(output, "</plist>")?;
+        Ok(())
+    }
+}
+
+#[test]
+fn test_write() {
+    use crate::{test_dictionary, test_entry};
+
+    let dictionary = test_dictionary(vec![test_entry("「R&D」", "あーるあんどでぃー", "R&D")]);
+    let mut output = Vec::new();
+    PlistWriter.write(&dictionary, &mut output).unwrap();
+    let output = String::from_utf8(output).unwrap();
+
+    assert!(output.contains("\t\t<string>「R&amp;D」</string>\n"));
+    assert!(output.contains("\t\t<string>あーるあんどでぃー</string>\n"));
+    assert!(output.ends_with("</array>\n</plist>\n"));
+}
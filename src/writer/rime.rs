@@ -0,0 +1,61 @@
+use std::io::{self, Write};
+
+use crate::Dictionary;
+
+use super::DictionaryWriter;
+
+/// Rimeの辞書 (`*.dict.yaml`) 形式で書き出す。
+///
+/// 出力は`<name>.dict.yaml`という名前で保存する必要がある。
+/// `version`にはデータベースダンプの日時を用いるので、Wikiが更新されると再デプロイされる。
+pub struct RimeDictWriter {
+    pub name: &'static str,
+}
+
+impl DictionaryWriter for RimeDictWriter {
+    fn write(&self, dictionary: &Dictionary, output: &mut dyn Write) -> io::Result<()> {
+        writeln!(output, "# Rime dictionary")?;
+        writeln!(output, "# encoding: utf-8")?;
+        writeln!(output, "---")?;
+        writeln!(output, "name: {}", self.name)?;
+        writeln!(
+            output,
+            "version: \"{}\"",
+            dictionary.timestamp.as_deref().unwrap_or("0")
+        )?;
+        writeln!(output, "sort: by_weight")?;
+        writeln!(output, "...")?;
+        writeln!(output)?;
+        for entry in &dictionary.entries {
+            writeln!(output, "{}\t{}\t1", entry.word, entry.yomi)?;
+        }
+        Ok(())
+    }
+}
+
+#[test]
+fn test_write() {
+    use crate::{test_dictionary, test_entry};
+
+    let dictionary = Dictionary {
+        timestamp: Some("2023-11-20T10:00:00Z".to_string()),
+        ..test_dictionary(vec![test_entry("璃月", "りーゆえ", "Liyue")])
+    };
+    let mut output = Vec::new();
+    RimeDictWriter { name: "genshin_ja" }
+        .write(&dictionary, &mut output)
+        .unwrap();
+
+    assert_eq!(
+        String::from_utf8(output).unwrap(),
+        "# Rime dictionary\n\
+         # encoding: utf-8\n\
+         ---\n\
+         name: genshin_ja\n\
+         version: \"2023-11-20T10:00:00Z\"\n\
+         sort: by_weight\n\
+         ...\n\
+         \n\
+         璃月\tりーゆえ\t1\n"
+    );
+}
@@ -0,0 +1,90 @@
+use std::{
+    collections::BTreeMap,
+    io::{self, Write},
+};
+
+use crate::{Dictionary, IMEDictionaryEntry};
+
+use super::DictionaryWriter;
+
+/// SKK辞書 (SKK-JISYO) 形式で書き出す。
+///
+/// すべての単語は送りなしエントリとして出力する。
+/// UTF-8の辞書では見出しがコードポイント順 (昇順) に並んでいる必要がある。
+pub struct SKKJisyoWriter;
+
+impl DictionaryWriter for SKKJisyoWriter {
+    fn write(&self, dictionary: &Dictionary, output: &mut dyn Write) -> io::Result<()> {
+        let mut candidates = BTreeMap::<&str, Vec<&IMEDictionaryEntry>>::new();
+        for entry in &dictionary.entries {
+            let list = candidates.entry(&entry.yomi).or_default();
+            if !list.iter().any(|c| c.word == entry.word) {
+                list.push(entry);
+            }
+        }
+
+        writeln!(output, ";; -*- mode: fundamental; coding: utf-8 -*-")?;
+        writeln!(output, ";; okuri-ari entries.")?;
+        writeln!(output, ";; okuri-nasi entries.")?;
+        for (yomi, list) in candidates {
+            write!(output, "{} /", yomi)?;
+            for entry in list {
+                if entry.page_titles.is_empty() {
+                    write!(output, "{}/", escape(&entry.word))?;
+                } else {
+                    write!(
+                        output,
+                        "{};{}/",
+                        escape(&entry.word),
+                        escape(&entry.page_titles.join(", "))
+                    )?;
+                }
+            }
+            writeln!(output)?;
+        }
+        Ok(())
+    }
+}
+
+/// 候補や注釈に`/`や`;`が含まれる場合、`(concat "...")`の形に変換する。
+fn escape(s: &str) -> String {
+    if !s.contains(['/', ';']) {
+        return s.to_string();
+    }
+
+    let mut escaped = String::from("(concat \"");
+    for c in s.chars() {
+        match c {
+            '/' => escaped.push_str("\\057"),
+            ';' => escaped.push_str("\\073"),
+            '"' => escaped.push_str("\\\""),
+            '\\' => escaped.push_str("\\\\"),
+            c => escaped.push(c),
+        }
+    }
+    escaped.push_str("\")");
+    escaped
+}
+
+#[test]
+fn test_write() {
+    use crate::{test_dictionary, test_entry};
+
+    let dictionary = test_dictionary(vec![
+        test_entry("璃月", "りーゆえ", "Liyue"),
+        test_entry("モンド", "もんど", "Mondstadt"),
+        test_entry("璃月", "りーゆえ", "Liyue Harbor"),
+        test_entry("離月", "りーゆえ", "A/B;C"),
+    ]);
+    let mut output = Vec::new();
+    SKKJisyoWriter.write(&dictionary, &mut output).unwrap();
+
+    assert_eq!(
+        String::from_utf8(output).unwrap(),
+        ";; -*- mode: fundamental; coding: utf-8 -*-\n\
+         ;; okuri-ari entries.\n\
+         ;; okuri-nasi entries.\n\
+         もんど /モンド;Mondstadt/\n\
+         りーゆえ /璃月;Liyue/離月;(concat \"A\\057B\\073C\")/\n"
+    );
+}
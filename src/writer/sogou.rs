@@ -0,0 +1,41 @@
+use std::io::{self, Write};
+
+use crate::Dictionary;
+
+use super::{write_utf16le, DictionaryWriter};
+
+/// 搜狗拼音のテキスト形式 (BOM付きUTF-16LE、CRLF改行) で書き出す。
+///
+/// 各行は`'li'yue 璃月`のように、`'`で区切った拼音と単語からなる。
+pub struct SogouWriter;
+
+impl DictionaryWriter for SogouWriter {
+    fn write(&self, dictionary: &Dictionary, output: &mut dyn Write) -> io::Result<()> {
+        let mut text = String::new();
+        for entry in &dictionary.entries {
+            for syllable in entry.yomi.split(' ') {
+                text.push('\'');
+                text.push_str(syllable);
+            }
+            text.push_str(&format!(" {}\r\n", entry.word));
+        }
+
+        write_utf16le(output, &text)
+    }
+}
+
+#[test]
+fn test_write() {
+    use crate::{test_dictionary, test_entry};
+
+    let dictionary = test_dictionary(vec![test_entry("璃月", "li yue", "Liyue")]);
+    let mut output = Vec::new();
+    SogouWriter.write(&dictionary, &mut output).unwrap();
+
+    assert_eq!(&output[..2], &[0xFF, 0xFE]);
+    let units = output[2..]
+        .chunks(2)
+        .map(|c| u16::from_le_bytes([c[0], c[1]]))
+        .collect::<Vec<_>>();
+    assert_eq!(String::from_utf16(&units).unwrap(), "'li'yue 璃月\r\n");
+}